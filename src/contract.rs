@@ -1,26 +1,42 @@
 use cosmwasm_std::{
-    log, to_binary, Api, CanonicalAddr, Env, Extern, HandleResponse, HandleResult, HumanAddr,
-    InitResponse, InitResult, Querier, QueryResult, StdError, Storage, Uint128,
+    from_binary, log, to_binary, Api, Binary, CanonicalAddr, CosmosMsg, Env, Extern,
+    HandleResponse, HandleResult, HumanAddr, InitResponse, InitResult, Querier, QueryResult,
+    StdError, StdResult, Storage, Uint128,
 };
 
 use std::collections::HashSet;
 
 use serde_json_wasm as serde_json;
 
-use secret_toolkit::utils::{pad_handle_result, pad_query_result};
+use secret_toolkit::{
+    crypto::sha_256,
+    snip20::{allowance_query, balance_query, set_viewing_key_msg, transfer_from_msg},
+    utils::{pad_handle_result, pad_query_result},
+};
 
 use crate::msg::{
-    HandleAnswer, HandleMsg, InitMsg, QueryAnswer, QueryMsg, ResponseStatus,
+    Asset, AuctionInfo, BidOptions, BidTokenRate, BundleItem, BundleToken, ConversionRate,
+    HandleAnswer, HandleMsg, InitMsg, NftInfo, OperatorPermission, PayoutCallback, PendingChange,
+    QueryAnswer, QueryMsg, ResponseStatus,
     ResponseStatus::{Failure, Success},
-    Token,
+    Snip1155Info, Token,
+};
+use crate::state::{
+    load, may_load, mul_div, remove, save, Bid, BidToken, Consignment, Consortium, Contribution,
+    HeldSale, LotItem, Operator, PendingPayment, State,
 };
-use crate::state::{load, may_load, remove, save, Bid, State};
 
 use chrono::NaiveDateTime;
 
 /// storage key for auction state
 pub const CONFIG_KEY: &[u8] = b"config";
 
+/// prefix for the storage keys of consortium bids
+pub const CONSORTIUM_PREFIX: &[u8] = b"consortium";
+
+/// denomination of native SCRT
+pub const NATIVE_DENOM: &str = "uscrt";
+
 /// pad handle responses and log attributes to blocks of 256 bytes to prevent leaking info based on
 /// response size
 pub const BLOCK_SIZE: usize = 256;
@@ -39,43 +55,463 @@ pub const BLOCK_SIZE: usize = 256;
 pub fn init<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    msg: InitMsg,
+    mut msg: InitMsg,
 ) -> InitResult {
-    if msg.sell_amount == Uint128(0) {
-        return Err(StdError::generic_err("Sell amount must be greater than 0"));
+    let lot: Vec<LotItem> = if let Some(goods) = &msg.off_chain_goods {
+        if msg.sell_contract.is_some()
+            || msg.sell_nft.is_some()
+            || msg.sell_snip1155.is_some()
+            || msg.bundle.is_some()
+            || msg.sell_amount.u128() != 0
+        {
+            return Err(StdError::generic_err(
+                "No sell token or sell amount can be provided when selling off-chain goods",
+            ));
+        }
+        if msg.dispute_period.is_some() {
+            return Err(StdError::generic_err(
+                "Off-chain goods use the delivery timeout instead of a dispute period",
+            ));
+        }
+        if goods.delivery_timeout == 0 {
+            return Err(StdError::generic_err(
+                "Delivery timeout must be greater than 0",
+            ));
+        }
+        Vec::new()
+    } else if let Some(bundle) = msg.bundle {
+        if msg.sell_contract.is_some()
+            || msg.sell_nft.is_some()
+            || msg.sell_snip1155.is_some()
+            || msg.sell_amount.u128() != 0
+        {
+            return Err(StdError::generic_err(
+                "Sell contract, sell NFT, sell SNIP-1155, and sell amount must be omitted when \
+                 selling a bundle",
+            ));
+        }
+        if bundle.is_empty() {
+            return Err(StdError::generic_err(
+                "Bundle must contain at least one asset",
+            ));
+        }
+        let mut lot = Vec::new();
+        for bundle_item in bundle {
+            let item = match bundle_item {
+                BundleItem::Snip20 { contract, amount } => {
+                    if amount == Uint128(0) {
+                        return Err(StdError::generic_err(
+                            "Sell amount of each bundled token must be greater than 0",
+                        ));
+                    }
+                    LotItem {
+                        asset: Asset::Snip20(contract),
+                        amount: amount.u128(),
+                        consigned: 0,
+                    }
+                }
+                BundleItem::Snip721(nft) => LotItem {
+                    asset: Asset::Snip721(nft),
+                    amount: 1,
+                    consigned: 0,
+                },
+                BundleItem::Snip1155 { token, amount } => {
+                    if amount == Uint128(0) {
+                        return Err(StdError::generic_err(
+                            "Sell amount of each bundled token must be greater than 0",
+                        ));
+                    }
+                    LotItem {
+                        asset: Asset::Snip1155(token),
+                        amount: amount.u128(),
+                        consigned: 0,
+                    }
+                }
+            };
+            if lot
+                .iter()
+                .any(|prev: &LotItem| prev.asset.conflicts_with(&item.asset))
+            {
+                return Err(StdError::generic_err(
+                    "Each SNIP-20 contract and NFT can only appear once in a bundle",
+                ));
+            }
+            lot.push(item);
+        }
+        lot
+    } else if let Some(nft) = msg.sell_nft {
+        if msg.sell_contract.is_some() || msg.sell_snip1155.is_some() {
+            return Err(StdError::generic_err(
+                "Can not sell both an NFT and fungible tokens",
+            ));
+        }
+        if msg.sell_amount.u128() > 1 {
+            return Err(StdError::generic_err("Sell amount of an NFT must be 1"));
+        }
+        vec![LotItem {
+            asset: Asset::Snip721(nft),
+            amount: 1,
+            consigned: 0,
+        }]
+    } else if let Some(token) = msg.sell_snip1155 {
+        if msg.sell_contract.is_some() {
+            return Err(StdError::generic_err(
+                "Can not sell both SNIP-1155 and SNIP-20 tokens",
+            ));
+        }
+        if msg.sell_amount == Uint128(0) {
+            return Err(StdError::generic_err("Sell amount must be greater than 0"));
+        }
+        vec![LotItem {
+            asset: Asset::Snip1155(token),
+            amount: msg.sell_amount.u128(),
+            consigned: 0,
+        }]
+    } else {
+        if msg.sell_amount == Uint128(0) {
+            return Err(StdError::generic_err("Sell amount must be greater than 0"));
+        }
+        vec![LotItem {
+            asset: Asset::from_contract(msg.sell_contract),
+            amount: msg.sell_amount.u128(),
+            consigned: 0,
+        }]
+    };
+    let bid_tokens: Vec<BidToken> = if let Some(accepted) = msg.bid_tokens {
+        if msg.bid_contract.is_some() {
+            return Err(StdError::generic_err(
+                "Bid contract must be omitted when accepting several bid tokens",
+            ));
+        }
+        if accepted.is_empty() {
+            return Err(StdError::generic_err(
+                "At least one bid token must be accepted",
+            ));
+        }
+        let mut bid_tokens = Vec::new();
+        for token in accepted {
+            if token.rate.numerator == Uint128(0) || token.rate.denominator == Uint128(0) {
+                return Err(StdError::generic_err(
+                    "Conversion rates must have a non-zero numerator and denominator",
+                ));
+            }
+            let asset = Asset::Snip20(token.contract);
+            if bid_tokens
+                .iter()
+                .any(|prev: &BidToken| prev.asset.conflicts_with(&asset))
+            {
+                return Err(StdError::generic_err(
+                    "Each bid token can only be listed once",
+                ));
+            }
+            bid_tokens.push(BidToken {
+                asset,
+                rate_numerator: token.rate.numerator.u128(),
+                rate_denominator: token.rate.denominator.u128(),
+            });
+        }
+        bid_tokens
+    } else {
+        vec![BidToken {
+            asset: Asset::from_contract(msg.bid_contract),
+            rate_numerator: 1,
+            rate_denominator: 1,
+        }]
+    };
+    if lot.iter().any(|item| {
+        bid_tokens
+            .iter()
+            .any(|bid_token| item.asset.conflicts_with(&bid_token.asset))
+    }) {
+        return Err(StdError::generic_err(
+            "Sell token and bid token must be different",
+        ));
+    }
+    if let Some(fee) = &msg.fee {
+        if fee.rate_bps > 10000 {
+            return Err(StdError::generic_err(
+                "Fee rate can not be more than 10000 basis points",
+            ));
+        }
+        deps.api.canonical_address(&fee.collector)?;
+    }
+    if let Some(vesting) = &msg.vesting {
+        if !single_fungible(&lot) || msg.consign_by_allowance {
+            return Err(StdError::generic_err(
+                "Vesting is only available when consigning a single fungible token to escrow",
+            ));
+        }
+        if vesting.duration == 0 || vesting.cliff > vesting.duration {
+            return Err(StdError::generic_err(
+                "Vesting duration must be greater than 0 and not less than the cliff",
+            ));
+        }
+    }
+    if msg.off_chain_goods.is_none() && msg.arbiter.is_some() != msg.dispute_period.is_some() {
+        return Err(StdError::generic_err(
+            "An arbiter and a dispute period must be provided together",
+        ));
+    }
+    if let Some(arbiter) = &msg.arbiter {
+        deps.api.canonical_address(arbiter)?;
+    }
+    if msg.arbiter.is_some() && msg.consign_by_allowance {
+        return Err(StdError::generic_err(
+            "Consigning by allowance can not be combined with a dispute period",
+        ));
+    }
+    if msg.deposit.is_some() && msg.consign_by_allowance {
+        return Err(StdError::generic_err(
+            "Consigning by allowance can not be combined with deposit-backed bids",
+        ));
+    }
+    if let Some(terms) = &msg.deposit {
+        if terms.rate_bps == 0 || terms.rate_bps > 10000 || terms.payment_window == 0 {
+            return Err(StdError::generic_err(
+                "Deposit rate must be between 1 and 10000 basis points and the payment window \
+                 must be greater than 0",
+            ));
+        }
+    }
+    if let Some(terms) = &msg.retraction {
+        if terms.fee_bps > 10000 {
+            return Err(StdError::generic_err(
+                "Retraction fee can not be more than 10000 basis points",
+            ));
+        }
+        if terms.to_fee_collector && msg.fee.is_none() {
+            return Err(StdError::generic_err(
+                "A retraction fee can only go to the fee collector if there is a platform fee",
+            ));
+        }
+        if terms.lock_period > 0 && terms.ends_at.is_none() {
+            return Err(StdError::generic_err(
+                "A retraction lock period requires a scheduled end time",
+            ));
+        }
+        if matches!(terms.ends_at, Some(ends_at) if ends_at <= env.block.time) {
+            return Err(StdError::generic_err(
+                "The scheduled end time must be in the future",
+            ));
+        }
+    }
+    if let Some(bid_fee) = &msg.bid_fee {
+        if bid_fee.amount == Uint128(0) {
+            return Err(StdError::generic_err("Bid fee must be greater than 0"));
+        }
+        if bid_fee.to_fee_collector && msg.fee.is_none() {
+            return Err(StdError::generic_err(
+                "A bid fee can only go to the fee collector if there is a platform fee",
+            ));
+        }
+    }
+    if matches!(msg.referral_rate_bps, Some(rate) if rate > 10000) {
+        return Err(StdError::generic_err(
+            "Referral rate can not be more than 10000 basis points",
+        ));
+    }
+    let mut referrers = Vec::new();
+    for referrer in msg.referrers.unwrap_or_default() {
+        referrers.push(deps.api.canonical_address(&referrer)?);
+    }
+    if msg.referral_rate_bps.is_some() && referrers.is_empty() {
+        return Err(StdError::generic_err(
+            "A referral rate requires a list of referrers",
+        ));
+    }
+    if msg.consortium_bids {
+        if !single_fungible(&lot) || msg.consign_by_allowance {
+            return Err(StdError::generic_err(
+                "Consortium bids are only available when consigning a single fungible token to \
+                 escrow",
+            ));
+        }
+        if msg.vesting.is_some() || msg.arbiter.is_some() || msg.deposit.is_some() {
+            return Err(StdError::generic_err(
+                "Consortium bids can not be combined with vesting, a dispute period, or \
+                 deposit-backed bids",
+            ));
+        }
+    }
+    let co_sellers = msg.co_sellers.unwrap_or_default();
+    for co_seller in co_sellers.iter() {
+        deps.api.canonical_address(co_seller)?;
+    }
+    if !co_sellers.is_empty()
+        && (!single_fungible(&lot)
+            || msg.consign_by_allowance
+            || msg.proceeds_stream_duration.is_some())
+    {
+        return Err(StdError::generic_err(
+            "Co-sellers are only available when consigning a single fungible token to escrow \
+             without streaming the proceeds",
+        ));
+    }
+    if msg.proceeds_stream_duration == Some(0) {
+        return Err(StdError::generic_err(
+            "Proceeds stream duration must be greater than 0",
+        ));
+    }
+    for address in msg
+        .proceeds_address
+        .iter()
+        .chain(msg.consignment_return_address.iter())
+    {
+        deps.api.canonical_address(address)?;
+    }
+    let payees = msg.payees.unwrap_or_default();
+    for payee in payees.iter() {
+        deps.api.canonical_address(&payee.address)?;
     }
-    if msg.sell_contract.address == msg.bid_contract.address {
+    if !payees.is_empty()
+        && payees
+            .iter()
+            .map(|payee| payee.share_bps as u32)
+            .sum::<u32>()
+            != 10000
+    {
         return Err(StdError::generic_err(
-            "Sell contract and bid contract must be different",
+            "Payee shares must add up to 10000 basis points",
         ));
     }
+    if let Some(admin) = &msg.admin {
+        deps.api.canonical_address(admin)?;
+    }
+    if let Some(approvers) = msg.approvers.as_mut() {
+        for approver in approvers.addresses.iter() {
+            deps.api.canonical_address(approver)?;
+        }
+        approvers.addresses.sort();
+        approvers.addresses.dedup();
+        if approvers.threshold == 0 || approvers.threshold as usize > approvers.addresses.len() {
+            return Err(StdError::generic_err(
+                "Approval threshold must be between 1 and the number of approvers",
+            ));
+        }
+    }
+    // the auction needs a viewing key with the sell token to check the seller's allowance.  The
+    // key can view the auction's balance, transfer history and allowances with the sell token, so
+    // it is hashed with the seller's entropy to keep it from being derived from public data
+    let allowance_key = if msg.consign_by_allowance {
+        if lot.len() != 1 || !matches!(lot[0].asset, Asset::Snip20(_)) {
+            return Err(StdError::generic_err(
+                "Consigning by allowance is only available when selling a single SNIP-20 token",
+            ));
+        }
+        let entropy = msg.entropy.as_ref().ok_or_else(|| {
+            StdError::generic_err("Consigning by allowance requires entropy for the viewing key")
+        })?;
+        let seed = format!(
+            "{}{}{}{}{}",
+            env.block.height, env.block.time, env.message.sender, env.contract.address, entropy
+        );
+        Some(Binary::from(&sha_256(seed.as_bytes())[..]).to_base64())
+    } else {
+        None
+    };
     let state = State {
         auction_addr: env.contract.address,
         seller: env.message.sender,
-        sell_contract: msg.sell_contract,
-        bid_contract: msg.bid_contract,
-        sell_amount: msg.sell_amount.u128(),
+        pending_seller: None,
+        operators: Vec::new(),
+        lot,
+        bid_tokens,
         minimum_bid: msg.minimum_bid.u128(),
-        currently_consigned: 0,
         bidders: HashSet::new(),
         is_completed: false,
-        tokens_consigned: false,
+        // off-chain goods have nothing to consign
+        tokens_consigned: msg.off_chain_goods.is_some(),
         description: msg.description,
         winning_bid: 0,
+        winning_bid_token: 0,
+        approvers: msg.approvers,
+        approvals: Vec::new(),
+        cancel_approvals: Vec::new(),
+        pending_change: None,
+        change_approvals: Vec::new(),
+        admin: msg.admin,
+        is_paused: false,
+        allowance_key,
+        seller_key: None,
+        fee: msg.fee,
+        fee_paid: 0,
+        payees,
+        proceeds_address: msg.proceeds_address,
+        consignment_return_address: msg.consignment_return_address,
+        proceeds_callback: validate_callback(&deps.api, msg.proceeds_callback)?,
+        winner: None,
+        winner_payout: None,
+        payout_unclaimed: false,
+        vesting: msg.vesting,
+        vesting_start: None,
+        vesting_claimed: 0,
+        stream_duration: msg.proceeds_stream_duration,
+        stream_start: None,
+        stream_total: 0,
+        stream_claimed: 0,
+        arbiter: msg.arbiter,
+        dispute_period: msg
+            .off_chain_goods
+            .as_ref()
+            .map_or(msg.dispute_period.unwrap_or(0), |goods| {
+                goods.delivery_timeout
+            }),
+        held_sale: None,
+        off_chain_goods: msg.off_chain_goods.map(|goods| goods.metadata),
+        deposit: msg.deposit,
+        awaiting_payment: None,
+        retraction: msg.retraction,
+        bid_fee: msg.bid_fee,
+        referral_rate_bps: msg.referral_rate_bps,
+        referrers,
+        referrer: None,
+        referral_paid: 0,
+        consortium_bids: msg.consortium_bids,
+        consortia: HashSet::new(),
+        winning_consortium: None,
+        co_sellers,
+        co_consignments: Vec::new(),
     };
 
     save(&mut deps.storage, CONFIG_KEY, &state)?;
 
     // register receive with the bid/sell token contracts
+    let mut messages = Vec::new();
+    let mut registered: Vec<HumanAddr> = Vec::new();
+    let tokens = state
+        .lot
+        .iter()
+        .map(|item| &item.asset)
+        .chain(state.bid_tokens.iter().map(|bid_token| &bid_token.asset));
+    for token in tokens {
+        let address = match token {
+            Asset::Snip20(info) => &info.address,
+            Asset::Snip721(nft) => &nft.contract.address,
+            Asset::Snip1155(token) => &token.contract.address,
+            Asset::Native => continue,
+        };
+        // several NFTs or SNIP-1155 tokens in a bundle can share a contract
+        if registered.contains(address) {
+            continue;
+        }
+        registered.push(address.clone());
+        if let Some(register) = token.register_receive_msg(env.contract_code_hash.clone())? {
+            messages.push(register);
+        }
+    }
+    // off-chain goods have an empty lot
+    let first_asset = state.lot.first().map(|item| &item.asset);
+    if let (Some(key), Some(Asset::Snip20(info))) = (&state.allowance_key, first_asset) {
+        messages.push(set_viewing_key_msg(
+            key.clone(),
+            None,
+            BLOCK_SIZE,
+            info.code_hash.clone(),
+            info.address.clone(),
+        )?);
+    }
     Ok(InitResponse {
-        messages: vec![
-            state
-                .sell_contract
-                .register_receive_msg(env.contract_code_hash.clone())?,
-            state
-                .bid_contract
-                .register_receive_msg(env.contract_code_hash)?,
-        ],
+        messages,
         log: vec![],
     })
 }
@@ -93,673 +529,5145 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
     env: Env,
     msg: HandleMsg,
 ) -> HandleResult {
+    // only place_bid and consign accept native SCRT, so refuse funds sent with anything else
+    if !env.message.sent_funds.is_empty()
+        && !matches!(msg, HandleMsg::PlaceBid { .. } | HandleMsg::Consign { .. })
+    {
+        return Err(StdError::generic_err(
+            "This message does not accept SCRT.  Your funds have been returned",
+        ));
+    }
     let response = match msg {
-        HandleMsg::RetractBid { .. } => try_retract(deps, env.message.sender),
-        HandleMsg::Finalize { only_if_bids, .. } => try_finalize(deps, env, only_if_bids, false),
-        HandleMsg::ReturnAll { .. } => try_finalize(deps, env, false, true),
-        HandleMsg::Receive { from, amount, .. } => try_receive(deps, env, from, amount),
+        HandleMsg::RetractBid { .. } => try_retract(deps, env),
+        HandleMsg::RetractContribution { consortium, .. } => {
+            try_retract_contribution(deps, env, consortium)
+        }
+        HandleMsg::Finalize { only_if_bids, .. } => {
+            try_finalize(deps, env, only_if_bids, false, false)
+        }
+        HandleMsg::CancelAuction { .. } => try_finalize(deps, env, false, false, true),
+        HandleMsg::ReturnAll { .. } => try_finalize(deps, env, false, true, false),
+        HandleMsg::Receive {
+            from, amount, msg, ..
+        } => try_receive(deps, env, from, amount, msg),
+        HandleMsg::ReceiveNft {
+            sender, token_id, ..
+        } => try_receive_nft(deps, env, sender, token_id),
+        HandleMsg::Snip1155Receive {
+            token_id,
+            from,
+            amount,
+            ..
+        } => try_receive_snip1155(deps, env, token_id, from, amount),
+        HandleMsg::PlaceBid {
+            payout,
+            bid_amount,
+            referrer,
+            consortium,
+            ..
+        } => try_native_deposit(
+            deps,
+            env,
+            false,
+            BidOptions {
+                payout,
+                bid_amount,
+                referrer,
+                consortium,
+            },
+        ),
+        HandleMsg::Consign { .. } => try_native_deposit(deps, env, true, BidOptions::default()),
+        HandleMsg::ConsignAllowance { balance_key, .. } => {
+            try_consign_allowance(deps, env, balance_key)
+        }
+        HandleMsg::WithdrawConsignment { amount, .. } => {
+            try_withdraw_consignment(deps, env, amount)
+        }
         HandleMsg::ViewBid { .. } => try_view_bid(deps, &env.message.sender),
+        HandleMsg::ViewReferral { .. } => try_view_referral(deps, &env.message.sender),
+        HandleMsg::ViewContribution { consortium, .. } => {
+            try_view_contribution(deps, &env.message.sender, consortium)
+        }
+        HandleMsg::ProposeSellerTransfer { address, .. } => {
+            try_propose_seller_transfer(deps, env, address)
+        }
+        HandleMsg::AcceptSellerTransfer { .. } => try_accept_seller_transfer(deps, env),
+        HandleMsg::ApproveChange { change, .. } => try_approve_change(deps, env, change),
+        HandleMsg::SetOperator {
+            address,
+            permissions,
+            ..
+        } => try_set_operator(deps, env, address, permissions),
+        HandleMsg::ApproveCoSeller { address, .. } => try_approve_co_seller(deps, env, address),
+        HandleMsg::Claim { plain_transfer, .. } => try_claim(deps, env, plain_transfer),
+        HandleMsg::ClaimProceeds { plain_transfer, .. } => {
+            try_claim_proceeds(deps, env, plain_transfer)
+        }
+        HandleMsg::DeclareDefault { .. } => try_declare_default(deps, env),
+        HandleMsg::Dispute { .. } => try_dispute(deps, env),
+        HandleMsg::ReleaseSale { plain_transfer, .. } => {
+            try_release_sale(deps, env, plain_transfer)
+        }
+        HandleMsg::ResolveDispute {
+            complete,
+            plain_transfer,
+            ..
+        } => try_resolve_dispute(deps, env, complete, plain_transfer),
+        HandleMsg::ConfirmDelivery { plain_transfer, .. } => {
+            try_confirm_delivery(deps, env, plain_transfer)
+        }
+        HandleMsg::ChangeDescription { description, .. } => {
+            try_change_description(deps, env, description)
+        }
+        HandleMsg::SetPaused { paused, .. } => try_set_paused(deps, env, paused),
+        HandleMsg::SetPayoutAddresses {
+            proceeds_address,
+            consignment_return_address,
+            proceeds_callback,
+            ..
+        } => try_set_payout_addresses(
+            deps,
+            env,
+            proceeds_address,
+            consignment_return_address,
+            proceeds_callback,
+        ),
     };
     pad_handle_result(response, BLOCK_SIZE)
 }
 
 /// Returns HandleResult
 ///
+/// nominate a new address to take over ownership of the auction
+///
 /// # Arguments
 ///
 /// * `deps` - mutable reference to Extern containing all the contract's external dependencies
-/// * `bidder` - reference to address wanting to view its bid
-fn try_view_bid<S: Storage, A: Api, Q: Querier>(
+/// * `env` - Env of contract's environment
+/// * `address` - address being proposed as the new seller
+fn try_propose_seller_transfer<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
-    bidder: &HumanAddr,
+    env: Env,
+    address: HumanAddr,
 ) -> HandleResult {
-    let state: State = load(&deps.storage, CONFIG_KEY)?;
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
 
-    let bidder_raw = &deps.api.canonical_address(bidder)?;
-    let mut amount_bid: Option<Uint128> = None;
-    let mut message = String::new();
     let status: ResponseStatus;
-
-    if state.bidders.contains(&bidder_raw.as_slice().to_vec()) {
-        let bid: Option<Bid> = may_load(&deps.storage, bidder_raw.as_slice())?;
-        if let Some(found_bid) = bid {
-            status = Success;
-            amount_bid = Some(Uint128(found_bid.amount));
-            message.push_str(&format!(
-                "Bid placed {} UTC",
-                NaiveDateTime::from_timestamp(found_bid.timestamp as i64, 0)
-                    .format("%Y-%m-%d %H:%M:%S")
-            ));
-        } else {
-            status = Failure;
-            message.push_str(&format!("No active bid for address: {}", bidder));
-        }
-    // no active bid found
-    } else {
+    let message: String;
+    // only the current seller can hand over the auction
+    if env.message.sender != state.seller {
         status = Failure;
-        message.push_str(&format!("No active bid for address: {}", bidder));
+        message = String::from("Only the auction creator can transfer ownership of the auction");
+    } else if state.approvers.is_some() {
+        message = propose_change(&mut state, PendingChange::SellerTransfer { address });
+        save(&mut deps.storage, CONFIG_KEY, &state)?;
+        status = Success;
+    } else {
+        message = format!(
+            "{} must call accept_seller_transfer to take over ownership of the auction",
+            address
+        );
+        state.pending_seller = Some(address);
+        save(&mut deps.storage, CONFIG_KEY, &state)?;
+        status = Success;
     }
     Ok(HandleResponse {
         messages: vec![],
         log: vec![],
-        data: Some(to_binary(&HandleAnswer::Bid {
-            status,
-            message,
-            previous_bid: None,
-            amount_bid,
-            amount_returned: None,
-        })?),
+        data: Some(to_binary(&HandleAnswer::Status { status, message })?),
     })
 }
 
 /// Returns HandleResult
 ///
-/// process the Receive message sent after either bid or sell token contract sent tokens to
-/// auction escrow
+/// accept a proposed transfer of auction ownership
 ///
 /// # Arguments
 ///
 /// * `deps` - mutable reference to Extern containing all the contract's external dependencies
 /// * `env` - Env of contract's environment
-/// * `from` - address of owner of tokens sent to escrow
-/// * `amount` - Uint128 amount sent to escrow
-fn try_receive<S: Storage, A: Api, Q: Querier>(
+fn try_accept_seller_transfer<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    from: HumanAddr,
-    amount: Uint128,
 ) -> HandleResult {
     let mut state: State = load(&deps.storage, CONFIG_KEY)?;
 
-    if env.message.sender == state.sell_contract.address {
-        try_consign(deps, from, amount, &mut state)
-    } else if env.message.sender == state.bid_contract.address {
-        try_bid(deps, env, from, amount, &mut state)
-    } else {
-        let message = format!(
-            "Address: {} is not a token in this auction",
+    let status: ResponseStatus;
+    let message: String;
+    // only the address that was proposed can accept ownership
+    if state.pending_seller.as_ref() != Some(&env.message.sender) {
+        status = Failure;
+        message = format!(
+            "Address: {} has not been proposed as the new auction owner",
             env.message.sender
         );
-        let resp = serde_json::to_string(&HandleAnswer::Status {
-            status: Failure,
-            message,
-        })
-        .unwrap();
-
-        Ok(HandleResponse {
-            messages: vec![],
-            log: vec![log("response", resp)],
-            data: None,
-        })
+    } else {
+        state.seller = env.message.sender;
+        state.pending_seller = None;
+        // the approvals to finalize were given for the previous owner's sale
+        state.clear_approvals();
+        // operators, payees and payout addresses were chosen by the previous owner
+        state.operators.clear();
+        state.payees.clear();
+        state.proceeds_address = None;
+        state.consignment_return_address = None;
+        state.proceeds_callback = None;
+        // an allowance granted by the previous owner can not be used to sell the new owner's
+        // tokens
+        if state.allowance_key.is_some() && !state.is_completed {
+            state.lot[0].consigned = 0;
+            state.tokens_consigned = false;
+            state.seller_key = None;
+        }
+        save(&mut deps.storage, CONFIG_KEY, &state)?;
+        status = Success;
+        message = String::from("You are now the owner of the auction");
     }
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Status { status, message })?),
+    })
 }
 
 /// Returns HandleResult
 ///
-/// process the attempt to consign sale tokens to auction escrow
+/// record an approver's consent to the pending change, and make the change once enough
+/// approvals have been collected
 ///
 /// # Arguments
 ///
 /// * `deps` - mutable reference to Extern containing all the contract's external dependencies
-/// * `owner` - address of owner of tokens sent to escrow
-/// * `amount` - Uint128 amount sent to escrow
-fn try_consign<S: Storage, A: Api, Q: Querier>(
+/// * `env` - Env of contract's environment
+/// * `change` - the change being approved
+fn try_approve_change<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
-    owner: HumanAddr,
-    amount: Uint128,
-    state: &mut State,
+    env: Env,
+    change: PendingChange,
 ) -> HandleResult {
-    // if not the auction owner, send the tokens back
-    if owner != state.seller {
-        let message = String::from(
-            "Only auction creator can consign tokens for sale.  Your tokens have been returned",
-        );
-
-        let resp = serde_json::to_string(&HandleAnswer::Consign {
-            status: Failure,
-            message,
-            amount_consigned: None,
-            amount_needed: None,
-            amount_returned: Some(amount),
-        })
-        .unwrap();
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
 
+    let threshold = match &state.approvers {
+        Some(approvers) if approvers.addresses.contains(&env.message.sender) => approvers.threshold,
+        _ => {
+            return Ok(HandleResponse {
+                messages: vec![],
+                log: vec![],
+                data: Some(to_binary(&HandleAnswer::Status {
+                    status: Failure,
+                    message: String::from("Only an approver can approve a change"),
+                })?),
+            });
+        }
+    };
+    // approvals are only counted for the exact change that is pending, so an approver can not
+    // consent to a change that replaced the one they reviewed
+    if state.pending_change.as_ref() != Some(&change) {
         return Ok(HandleResponse {
-            messages: vec![state.sell_contract.transfer_msg(owner, amount)?],
-            log: vec![log("response", resp)],
-            data: None,
+            messages: vec![],
+            log: vec![],
+            data: Some(to_binary(&HandleAnswer::Status {
+                status: Failure,
+                message: String::from("That change is not waiting for approval"),
+            })?),
         });
     }
-    // if auction is over, send the tokens back
-    if state.is_completed {
-        let message = String::from("Auction has ended. Your tokens have been returned");
-
-        let resp = serde_json::to_string(&HandleAnswer::Consign {
-            status: Failure,
-            message,
-            amount_consigned: None,
-            amount_needed: None,
-            amount_returned: Some(amount),
-        })
-        .unwrap();
-
+    if !state.change_approvals.contains(&env.message.sender) {
+        state.change_approvals.push(env.message.sender);
+    }
+    let collected = state.change_approvals.len();
+    if (collected as u32) < threshold {
+        save(&mut deps.storage, CONFIG_KEY, &state)?;
         return Ok(HandleResponse {
-            messages: vec![state.sell_contract.transfer_msg(owner, amount)?],
-            log: vec![log("response", resp)],
-            data: None,
+            messages: vec![],
+            log: vec![],
+            data: Some(to_binary(&HandleAnswer::Status {
+                status: Success,
+                message: format!(
+                    "Approval recorded.  {} of {} required approvals have been collected",
+                    collected, threshold
+                ),
+            })?),
         });
     }
-    // if tokens to be sold have already been consigned, return these tokens
-    if state.tokens_consigned {
-        let message = String::from(
-            "Tokens to be sold have already been consigned. Your tokens have been returned",
-        );
-
-        let resp = serde_json::to_string(&HandleAnswer::Consign {
-            status: Failure,
-            message,
-            amount_consigned: Some(Uint128(state.currently_consigned)),
-            amount_needed: None,
-            amount_returned: Some(amount),
-        })
-        .unwrap();
-
-        return Ok(HandleResponse {
-            messages: vec![state.sell_contract.transfer_msg(owner, amount)?],
-            log: vec![log("response", resp)],
-            data: None,
-        });
-    }
-
-    let consign_total = state.currently_consigned + amount.u128();
-    let mut log_msg = String::new();
-    let mut cos_msg = Vec::new();
-    let status: ResponseStatus;
-    let mut excess: Option<Uint128> = None;
-    let mut needed: Option<Uint128> = None;
-    // if consignment amount < auction sell amount, ask for remaining balance
-    if consign_total < state.sell_amount {
-        state.currently_consigned = consign_total;
-        needed = Some(Uint128(state.sell_amount - consign_total));
-        status = Failure;
-        log_msg.push_str(
-            "You have not consigned the full amount to be sold.  You need to consign additional \
-             tokens",
-        );
-    // all tokens to be sold have been consigned
-    } else {
-        state.tokens_consigned = true;
-        state.currently_consigned = state.sell_amount;
-        status = Success;
-        log_msg.push_str("Tokens to be sold have been consigned to the auction");
-        // if consigned more than needed, return excess tokens
-        if consign_total > state.sell_amount {
-            excess = Some(Uint128(consign_total - state.sell_amount));
-            cos_msg.push(state.sell_contract.transfer_msg(owner, excess.unwrap())?);
-            log_msg.push_str(".  Excess tokens have been returned");
+    state.pending_change = None;
+    state.change_approvals.clear();
+    let mut status = Success;
+    let message = match change {
+        PendingChange::SellerTransfer { address } => {
+            let message = format!(
+                "{} must call accept_seller_transfer to take over ownership of the auction",
+                address
+            );
+            state.pending_seller = Some(address);
+            message
         }
-    }
-
+        // the auction may have closed while the change was waiting for approval
+        PendingChange::PayoutAddresses { .. } if state.is_completed => {
+            status = Failure;
+            String::from("Payout addresses can not be changed after the auction has closed")
+        }
+        PendingChange::PayoutAddresses {
+            proceeds_address,
+            consignment_return_address,
+            proceeds_callback,
+        } => set_payout_addresses(
+            &mut state,
+            proceeds_address,
+            consignment_return_address,
+            proceeds_callback,
+        ),
+        // the withdrawal answers with the consignment left in escrow
+        PendingChange::WithdrawConsignment { consignor, amount } => {
+            save(&mut deps.storage, CONFIG_KEY, &state)?;
+            return withdraw_consignment(deps, state, consignor, amount);
+        }
+    };
     save(&mut deps.storage, CONFIG_KEY, &state)?;
 
-    let resp = serde_json::to_string(&HandleAnswer::Consign {
-        status,
-        message: log_msg,
-        amount_consigned: Some(Uint128(state.currently_consigned)),
-        amount_needed: needed,
-        amount_returned: excess,
-    })
-    .unwrap();
-
     Ok(HandleResponse {
-        messages: cos_msg,
-        log: vec![log("response", resp)],
-        data: None,
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Status { status, message })?),
     })
 }
 
+/// Returns String describing the change that now waits for the approvers' consent
+///
+/// # Arguments
+///
+/// * `state` - a mutable reference to the auction State
+/// * `change` - the change that needs the approvers' consent
+fn propose_change(state: &mut State, change: PendingChange) -> String {
+    // a new proposal replaces any earlier one, and its approvals start over
+    state.pending_change = Some(change);
+    state.change_approvals.clear();
+    String::from("The change is waiting for the approvers to call approve_change")
+}
+
 /// Returns HandleResult
 ///
-/// process the bid attempt
+/// add, update, or remove an operator
 ///
 /// # Arguments
 ///
 /// * `deps` - mutable reference to Extern containing all the contract's external dependencies
 /// * `env` - Env of contract's environment
-/// * `bidder` - address of owner of tokens sent to escrow
-/// * `amount` - Uint128 amount sent to escrow
-/// * `state` - mutable reference to auction state
-fn try_bid<S: Storage, A: Api, Q: Querier>(
+/// * `address` - address of the operator
+/// * `permissions` - actions the operator is allowed to perform
+fn try_set_operator<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    bidder: HumanAddr,
-    amount: Uint128,
-    state: &mut State,
+    address: HumanAddr,
+    permissions: Vec<OperatorPermission>,
 ) -> HandleResult {
-    // if auction is over, send the tokens back
-    if state.is_completed {
-        let message = String::from("Auction has ended. Bid tokens have been returned");
-
-        let resp = serde_json::to_string(&HandleAnswer::Bid {
-            status: Failure,
-            message,
-            previous_bid: None,
-            amount_bid: None,
-            amount_returned: Some(amount),
-        })
-        .unwrap();
-
-        return Ok(HandleResponse {
-            messages: vec![state.bid_contract.transfer_msg(bidder, amount)?],
-            log: vec![log("response", resp)],
-            data: None,
-        });
-    }
-    // don't accept a 0 bid
-    if amount == Uint128(0) {
-        let message = String::from("Bid must be greater than 0");
-
-        let resp = serde_json::to_string(&HandleAnswer::Bid {
-            status: Failure,
-            message,
-            previous_bid: None,
-            amount_bid: None,
-            amount_returned: None,
-        })
-        .unwrap();
-
-        return Ok(HandleResponse {
-            messages: vec![],
-            log: vec![log("response", resp)],
-            data: None,
-        });
-    }
-    // if bid is less than the minimum accepted bid, send the tokens back
-    if amount.u128() < state.minimum_bid {
-        let message =
-            String::from("Bid was less than minimum allowed.  Bid tokens have been returned");
-
-        let resp = serde_json::to_string(&HandleAnswer::Bid {
-            status: Failure,
-            message,
-            previous_bid: None,
-            amount_bid: None,
-            amount_returned: Some(amount),
-        })
-        .unwrap();
-
-        return Ok(HandleResponse {
-            messages: vec![state.bid_contract.transfer_msg(bidder, amount)?],
-            log: vec![log("response", resp)],
-            data: None,
-        });
-    }
-    let mut return_amount: Option<Uint128> = None;
-    let bidder_raw = &deps.api.canonical_address(&bidder)?;
-
-    // if there is an active bid from this address
-    if state.bidders.contains(&bidder_raw.as_slice().to_vec()) {
-        let bid: Option<Bid> = may_load(&deps.storage, bidder_raw.as_slice())?;
-        if let Some(old_bid) = bid {
-            // if new bid is <= the old bid, keep old bid and return this one
-            if amount.u128() <= old_bid.amount {
-                let message = String::from(
-                    "New bid less than or equal to previous bid. Newly bid tokens have been \
-                     returned",
-                );
-
-                let resp = serde_json::to_string(&HandleAnswer::Bid {
-                    status: Failure,
-                    message,
-                    previous_bid: Some(Uint128(old_bid.amount)),
-                    amount_bid: None,
-                    amount_returned: Some(amount),
-                })
-                .unwrap();
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
 
-                return Ok(HandleResponse {
-                    messages: vec![state.bid_contract.transfer_msg(bidder, amount)?],
-                    log: vec![log("response", resp)],
-                    data: None,
-                });
-            // new bid is larger, save the new bid, and return the old one, so mark for return
-            } else {
-                return_amount = Some(Uint128(old_bid.amount));
-            }
-        }
-    // address did not have an active bid
+    let status: ResponseStatus;
+    let message: String;
+    // only the seller can delegate permissions
+    if env.message.sender != state.seller {
+        status = Failure;
+        message = String::from("Only the auction creator can set operators");
     } else {
-        // insert in list of bidders and save
-        state.bidders.insert(bidder_raw.as_slice().to_vec());
+        state.operators.retain(|op| op.address != address);
+        message = if permissions.is_empty() {
+            format!("{} is no longer an operator", address)
+        } else {
+            format!("{} has been authorized as an operator", address)
+        };
+        if !permissions.is_empty() {
+            state.operators.push(Operator {
+                address,
+                permissions,
+            });
+        }
         save(&mut deps.storage, CONFIG_KEY, &state)?;
+        status = Success;
     }
-    let new_bid = Bid {
-        amount: amount.u128(),
-        timestamp: env.block.time,
-    };
-    save(&mut deps.storage, bidder_raw.as_slice(), &new_bid)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Status { status, message })?),
+    })
+}
 
-    let mut message = String::from("Bid accepted");
-    let mut cos_msg = Vec::new();
+/// Returns HandleResult
+///
+/// approve an address to consign sell tokens alongside the seller
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `address` - address of the co-seller
+fn try_approve_co_seller<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    address: HumanAddr,
+) -> HandleResult {
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
 
-    // if need to return the old bid
-    if let Some(returned) = return_amount {
-        cos_msg.push(state.bid_contract.transfer_msg(bidder, returned)?);
-        message.push_str(". Previously bid tokens have been returned");
+    let status: ResponseStatus;
+    let message: String;
+    if env.message.sender != state.seller {
+        status = Failure;
+        message = String::from("Only the auction creator can approve co-sellers");
+    } else if state.is_completed {
+        status = Failure;
+        message = String::from("Co-sellers can not be approved after the auction has closed");
+    } else if !single_fungible(&state.lot)
+        || state.allowance_key.is_some()
+        || state.stream_duration.is_some()
+    {
+        status = Failure;
+        message = String::from(
+            "Co-sellers are only available when consigning a single fungible token to escrow \
+             without streaming the proceeds",
+        );
+    } else {
+        deps.api.canonical_address(&address)?;
+        if address != state.seller && !state.co_sellers.contains(&address) {
+            state.co_sellers.push(address.clone());
+            save(&mut deps.storage, CONFIG_KEY, &state)?;
+        }
+        status = Success;
+        message = format!("{} has been approved as a co-seller", address);
     }
-    let resp = serde_json::to_string(&HandleAnswer::Bid {
-        status: Success,
-        message,
-        previous_bid: None,
-        amount_bid: Some(amount),
-        amount_returned: return_amount,
-    })
-    .unwrap();
-
     Ok(HandleResponse {
-        messages: cos_msg,
-        log: vec![log("response", resp)],
-        data: None,
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Status { status, message })?),
     })
 }
 
 /// Returns HandleResult
 ///
-/// attempt to retract current bid
+/// change the auction description
 ///
 /// # Arguments
 ///
 /// * `deps` - mutable reference to Extern containing all the contract's external dependencies
-/// * `bidder` - address of bidder
-fn try_retract<S: Storage, A: Api, Q: Querier>(
+/// * `env` - Env of contract's environment
+/// * `description` - optional new description
+fn try_change_description<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
-    bidder: HumanAddr,
+    env: Env,
+    description: Option<String>,
 ) -> HandleResult {
     let mut state: State = load(&deps.storage, CONFIG_KEY)?;
 
-    let bidder_raw = &deps.api.canonical_address(&bidder)?;
-    let mut cos_msg = Vec::new();
-    let sent: Option<Uint128>;
-    let mut log_msg = String::new();
     let status: ResponseStatus;
-    // if there was a active bid from this address, remove the bid and return tokens
-    if state.bidders.contains(&bidder_raw.as_slice().to_vec()) {
-        let bid: Option<Bid> = may_load(&deps.storage, bidder_raw.as_slice())?;
-        if let Some(old_bid) = bid {
-            remove(&mut deps.storage, bidder_raw.as_slice());
-            state.bidders.remove(&bidder_raw.as_slice().to_vec());
-            save(&mut deps.storage, CONFIG_KEY, &state)?;
-            cos_msg.push(
-                state
-                    .bid_contract
-                    .transfer_msg(bidder, Uint128(old_bid.amount))?,
-            );
-            status = Success;
-            sent = Some(Uint128(old_bid.amount));
-            log_msg.push_str("Bid retracted.  Tokens have been returned");
-        } else {
-            status = Failure;
-            sent = None;
-            log_msg.push_str(&format!("No active bid for address: {}", bidder));
-        }
-    // no active bid found
-    } else {
+    let message: String;
+    if !state.is_authorized(&env.message.sender, OperatorPermission::UpdateDescription) {
         status = Failure;
-        sent = None;
-        log_msg.push_str(&format!("No active bid for address: {}", bidder));
+        message = String::from("You are not authorized to change the auction description");
+    } else if state.is_completed {
+        status = Failure;
+        message = String::from("Auction has ended.  The description can not be changed");
+    } else {
+        state.description = description;
+        save(&mut deps.storage, CONFIG_KEY, &state)?;
+        status = Success;
+        message = String::from("Auction description has been changed");
     }
     Ok(HandleResponse {
-        messages: cos_msg,
+        messages: vec![],
         log: vec![],
-        data: Some(to_binary(&HandleAnswer::RetractBid {
-            status,
-            message: log_msg,
-            amount_returned: sent,
-        })?),
+        data: Some(to_binary(&HandleAnswer::Status { status, message })?),
     })
 }
 
 /// Returns HandleResult
 ///
-/// closes the auction and sends all the tokens in escrow to where they belong
+/// pause or resume accepting new bids and consignment
 ///
 /// # Arguments
 ///
 /// * `deps` - mutable reference to Extern containing all the contract's external dependencies
 /// * `env` - Env of contract's environment
-/// * `only_if_bids` - true if auction should stay open if there are no bids
-/// * `return_all` - true if being called from the return_all fallback plan
-fn try_finalize<S: Storage, A: Api, Q: Querier>(
+/// * `paused` - true if new deposits should be refused
+fn try_set_paused<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    only_if_bids: bool,
-    return_all: bool,
+    paused: bool,
 ) -> HandleResult {
     let mut state: State = load(&deps.storage, CONFIG_KEY)?;
 
-    // can only do a return_all if the auction is closed
-    if return_all && !state.is_completed {
-        return Ok(HandleResponse {
-            messages: vec![],
-            log: vec![],
-            data: Some(to_binary(&HandleAnswer::CloseAuction {
-                status: Failure,
-                message: String::from(
-                    "return_all can only be executed after the auction has ended",
-                ),
-                winning_bid: None,
-                amount_returned: None,
-            })?),
-        });
-    }
-    // if not the auction owner, can't finalize, but you can return_all
-    if !return_all && env.message.sender != state.seller {
-        return Ok(HandleResponse {
-            messages: vec![],
-            log: vec![],
-            data: Some(to_binary(&HandleAnswer::CloseAuction {
-                status: Failure,
-                message: String::from("Only auction creator can finalize the sale"),
-                winning_bid: None,
-                amount_returned: None,
-            })?),
-        });
-    }
-    // if there are no active bids, and owner only wants to close if bids
-    if !state.is_completed && only_if_bids && state.bidders.is_empty() {
-        return Ok(HandleResponse {
-            messages: vec![],
-            log: vec![],
-            data: Some(to_binary(&HandleAnswer::CloseAuction {
-                status: Failure,
-                message: String::from("Did not close because there are no active bids"),
-                winning_bid: None,
-                amount_returned: None,
-            })?),
-        });
+    let status: ResponseStatus;
+    let message: String;
+    // only the seller or the admin can pause
+    if env.message.sender != state.seller && state.admin.as_ref() != Some(&env.message.sender) {
+        status = Failure;
+        message = String::from("Only the auction creator or admin can pause the auction");
+    } else {
+        state.is_paused = paused;
+        save(&mut deps.storage, CONFIG_KEY, &state)?;
+        status = Success;
+        message = if paused {
+            String::from("Auction has been paused.  New bids and consignment will be returned")
+        } else {
+            String::from("Auction has been resumed")
+        };
     }
-    let mut cos_msg = Vec::new();
-    let mut update_state = false;
-    let mut winning_amount: Option<Uint128> = None;
-    let mut amount_returned: Option<Uint128> = None;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Status { status, message })?),
+    })
+}
 
-    let no_bids = state.bidders.is_empty();
-    // if there were bids
-    if !no_bids {
-        // load all the bids
-        struct OwnedBid {
-            pub bidder: CanonicalAddr,
-            pub bid: Bid,
-        }
-        let mut bid_list: Vec<OwnedBid> = Vec::new();
-        for bidder in &state.bidders {
-            let bid: Option<Bid> = may_load(&deps.storage, bidder.as_slice())?;
-            if let Some(found_bid) = bid {
-                bid_list.push(OwnedBid {
-                    bidder: CanonicalAddr::from(bidder.as_slice()),
-                    bid: found_bid,
-                });
-            }
-        }
-        // closing an auction that has been fully consigned
-        if state.tokens_consigned && !state.is_completed {
-            bid_list.sort_by(|a, b| {
-                a.bid
-                    .amount
-                    .cmp(&b.bid.amount)
-                    .then(b.bid.timestamp.cmp(&a.bid.timestamp))
-            });
-            // if there was a winner, swap the tokens
-            if let Some(winning_bid) = bid_list.pop() {
-                cos_msg.push(
-                    state
-                        .bid_contract
-                        .transfer_msg(state.seller.clone(), Uint128(winning_bid.bid.amount))?,
-                );
-                cos_msg.push(state.sell_contract.transfer_msg(
-                    deps.api.human_address(&winning_bid.bidder)?,
-                    Uint128(state.sell_amount),
-                )?);
-                state.currently_consigned = 0;
-                update_state = true;
-                winning_amount = Some(Uint128(winning_bid.bid.amount));
-                state.winning_bid = winning_bid.bid.amount;
-                remove(&mut deps.storage, &winning_bid.bidder.as_slice());
-                state
-                    .bidders
-                    .remove(&winning_bid.bidder.as_slice().to_vec());
-            }
-        }
-        // loops through all remaining bids to return them to the bidders
-        for losing_bid in &bid_list {
-            cos_msg.push(state.bid_contract.transfer_msg(
-                deps.api.human_address(&losing_bid.bidder)?,
-                Uint128(losing_bid.bid.amount),
-            )?);
-            remove(&mut deps.storage, &losing_bid.bidder.as_slice());
-            update_state = true;
-            state.bidders.remove(&losing_bid.bidder.as_slice().to_vec());
-        }
-    }
-    // return any tokens that have been consigned to the auction owner (can happen if owner
-    // finalized the auction before consigning the full sale amount or if there were no bids)
-    if state.currently_consigned > 0 {
-        cos_msg.push(
-            state
-                .sell_contract
-                .transfer_msg(state.seller.clone(), Uint128(state.currently_consigned))?,
-        );
-        if !return_all {
-            amount_returned = Some(Uint128(state.currently_consigned));
+/// Returns HandleResult
+///
+/// change where the proceeds are sent and where unsold consignment is returned
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `proceeds_address` - optional address that receives the proceeds instead of the seller
+/// * `consignment_return_address` - optional address that unsold consignment is returned to
+/// * `proceeds_callback` - optional code hash and message used to Send the proceeds
+fn try_set_payout_addresses<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    proceeds_address: Option<HumanAddr>,
+    consignment_return_address: Option<HumanAddr>,
+    proceeds_callback: Option<PayoutCallback>,
+) -> HandleResult {
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    let status: ResponseStatus;
+    let message: String;
+    if env.message.sender != state.seller {
+        status = Failure;
+        message = String::from("Only the auction creator can change the payout addresses");
+    } else if state.is_completed {
+        status = Failure;
+        message = String::from("Payout addresses can not be changed after the auction has closed");
+    } else {
+        for address in proceeds_address
+            .iter()
+            .chain(consignment_return_address.iter())
+        {
+            deps.api.canonical_address(address)?;
         }
-        state.currently_consigned = 0;
-        update_state = true;
-    }
-    // mark that auction had ended
-    if !state.is_completed {
-        state.is_completed = true;
-        update_state = true;
-    }
-    if update_state {
+        let proceeds_callback = validate_callback(&deps.api, proceeds_callback)?;
+        message = if state.approvers.is_some() {
+            propose_change(
+                &mut state,
+                PendingChange::PayoutAddresses {
+                    proceeds_address,
+                    consignment_return_address,
+                    proceeds_callback,
+                },
+            )
+        } else {
+            set_payout_addresses(
+                &mut state,
+                proceeds_address,
+                consignment_return_address,
+                proceeds_callback,
+            )
+        };
         save(&mut deps.storage, CONFIG_KEY, &state)?;
+        status = Success;
     }
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Status { status, message })?),
+    })
+}
 
-    let log_msg = if winning_amount.is_some() {
-        "Sale finalized.  You have been sent the winning bid tokens".to_string()
-    } else if amount_returned.is_some() {
-        let cause = if !state.tokens_consigned {
-            " because you did not consign the full sale amount"
-        } else if no_bids {
-            " because there were no active bids"
+/// Returns String describing the updated payout addresses
+///
+/// # Arguments
+///
+/// * `state` - a mutable reference to the auction State
+/// * `proceeds_address` - optional address that receives the proceeds instead of the seller
+/// * `consignment_return_address` - optional address that unsold consignment is returned to
+/// * `proceeds_callback` - optional validated code hash and message used to Send the proceeds
+fn set_payout_addresses(
+    state: &mut State,
+    proceeds_address: Option<HumanAddr>,
+    consignment_return_address: Option<HumanAddr>,
+    proceeds_callback: Option<PayoutCallback>,
+) -> String {
+    state.proceeds_address = proceeds_address;
+    state.consignment_return_address = consignment_return_address;
+    state.proceeds_callback = proceeds_callback;
+    // the approvals to finalize were given for the sale as it was paid out before
+    state.clear_approvals();
+    String::from("Payout addresses have been updated")
+}
+
+/// Returns HandleResult
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `referrer` - reference to address wanting to view its referral reward
+fn try_view_referral<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    referrer: &HumanAddr,
+) -> HandleResult {
+    let state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    let answer = if state.referral_rate_bps.is_none() {
+        HandleAnswer::Referral {
+            status: Failure,
+            message: String::from("This auction does not pay referral rewards"),
+            referral_rate_bps: None,
+            earned: None,
+        }
+    } else {
+        let earned = if state.referrer.as_ref() == Some(referrer) {
+            state.referral_paid
         } else {
-            ""
+            0
         };
-        format!(
-            "Auction closed.  You have been returned the consigned tokens{}",
-            cause
-        )
-    } else if return_all {
-        "Outstanding funds have been returned".to_string()
-    } else {
-        "Auction has been closed".to_string()
+        HandleAnswer::Referral {
+            status: Success,
+            message: format!("Referral reward earned by {}", referrer),
+            referral_rate_bps: state.referral_rate_bps,
+            earned: Some(Uint128(earned)),
+        }
     };
     Ok(HandleResponse {
-        messages: cos_msg,
+        messages: vec![],
         log: vec![],
-        data: Some(to_binary(&HandleAnswer::CloseAuction {
-            status: Success,
-            message: log_msg,
-            winning_bid: winning_amount,
-            amount_returned,
-        })?),
+        data: Some(to_binary(&answer)?),
     })
 }
 
-/////////////////////////////////////// Query /////////////////////////////////////
-/// Returns QueryResult
+/// Returns HandleResult
 ///
 /// # Arguments
 ///
-/// * `deps` - reference to Extern containing all the contract's external dependencies
-/// * `msg` - QueryMsg passed in with the query call
-pub fn query<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, msg: QueryMsg) -> QueryResult {
-    let response = match msg {
-        QueryMsg::AuctionInfo { .. } => try_query_info(deps),
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `contributor` - reference to address wanting to view its contribution
+/// * `consortium` - id of the consortium
+fn try_view_contribution<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    contributor: &HumanAddr,
+    consortium: String,
+) -> HandleResult {
+    let state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    let pool: Option<Consortium> = may_load(&deps.storage, &consortium_key(&consortium))?;
+    let found = pool.and_then(|pool| {
+        let index = pool
+            .contributions
+            .iter()
+            .position(|c| c.contributor == *contributor)?;
+        Some((pool, index))
+    });
+    let answer = if let Some((pool, index)) = found {
+        let share = pool.shares(state.lot[0].amount)?[index];
+        let message = if state.winning_consortium.as_ref() == Some(&consortium) {
+            "Consortium won the auction"
+        } else {
+            "Consortium bid is active"
+        };
+        HandleAnswer::Contribution {
+            status: Success,
+            message: String::from(message),
+            total: Some(Uint128(pool.bid.amount)),
+            contribution: Some(Uint128(pool.contributions[index].amount)),
+            share: Some(Uint128(share)),
+        }
+    } else {
+        HandleAnswer::Contribution {
+            status: Failure,
+            message: format!(
+                "No contribution from address {} to consortium {}",
+                contributor, consortium
+            ),
+            total: None,
+            contribution: None,
+            share: None,
+        }
     };
-    pad_query_result(response, BLOCK_SIZE)
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&answer)?),
+    })
 }
 
-/// Returns QueryResult
+/// Returns HandleResult
 ///
 /// # Arguments
 ///
-/// * `deps` - reference to Extern containing all the contract's external dependencies
-fn try_query_info<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> QueryResult {
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `bidder` - reference to address wanting to view its bid
+fn try_view_bid<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    bidder: &HumanAddr,
+) -> HandleResult {
     let state: State = load(&deps.storage, CONFIG_KEY)?;
 
-    // get sell token info
-    let sell_token_info = state.sell_contract.token_info_query(&deps.querier)?;
-    // get bid token info
-    let bid_token_info = state.bid_contract.token_info_query(&deps.querier)?;
+    let bidder_raw = &deps.api.canonical_address(bidder)?;
+    let mut amount_bid: Option<Uint128> = None;
+    let mut message = String::new();
+    let status: ResponseStatus;
 
-    // build status string
-    let status = if state.is_completed {
-        let locked = if !state.bidders.is_empty() || state.currently_consigned > 0 {
-            ", but found outstanding balances.  Please run either retract_bid to \
-                retrieve your non-winning bid, or return_all to return all outstanding bids/\
-                consignment."
+    if state.bidders.contains(&bidder_raw.as_slice().to_vec()) {
+        let bid: Option<Bid> = may_load(&deps.storage, bidder_raw.as_slice())?;
+        if let Some(found_bid) = bid {
+            status = Success;
+            amount_bid = Some(Uint128(found_bid.amount));
+            message.push_str(&format!(
+                "Bid placed {} UTC",
+                NaiveDateTime::from_timestamp(found_bid.timestamp as i64, 0)
+                    .format("%Y-%m-%d %H:%M:%S")
+            ));
+            // let the bidder know which token they bid if there are several
+            if let (true, Asset::Snip20(info)) = (
+                state.bid_tokens.len() > 1,
+                &state.bid_tokens[found_bid.token].asset,
+            ) {
+                message.push_str(&format!(" in token {}", info.address));
+            }
         } else {
-            ""
-        };
-        format!("Closed{}", locked)
+            status = Failure;
+            message.push_str(&format!("No active bid for address: {}", bidder));
+        }
+    // no active bid found
     } else {
-        let consign = if !state.tokens_consigned { " NOT" } else { "" };
-        format!(
-            "Accepting bids: Token(s) to be sold have{} been consigned to the auction",
-            consign
-        )
-    };
+        status = Failure;
+        message.push_str(&format!("No active bid for address: {}", bidder));
+    }
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Bid {
+            status,
+            message,
+            previous_bid: None,
+            amount_bid,
+            amount_returned: None,
+            deposit: None,
+            fee: None,
+        })?),
+    })
+}
 
-    let winning_bid = if state.winning_bid == 0 {
-        None
-    } else {
-        Some(Uint128(state.winning_bid))
-    };
+/// Returns HandleResult
+///
+/// process the Receive message sent after either bid or sell token contract sent tokens to
+/// auction escrow
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `from` - address of owner of tokens sent to escrow
+/// * `amount` - Uint128 amount sent to escrow
+/// * `msg` - optional base64 encoded BidOptions sent with the bid
+fn try_receive<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    from: HumanAddr,
+    amount: Uint128,
+    msg: Option<Binary>,
+) -> HandleResult {
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
 
-    to_binary(&QueryAnswer::AuctionInfo {
-        sell_token: Token {
-            contract_address: state.sell_contract.address,
-            token_info: sell_token_info,
-        },
-        bid_token: Token {
-            contract_address: state.bid_contract.address,
-            token_info: bid_token_info,
-        },
-        sell_amount: Uint128(state.sell_amount),
-        minimum_bid: Uint128(state.minimum_bid),
-        description: state.description,
-        auction_address: state.auction_addr,
-        status,
-        winning_bid,
+    if let Some(item) = state
+        .lot
+        .iter()
+        .position(|item| item.asset.is_snip20(&env.message.sender))
+    {
+        try_consign(deps, from, amount, item, &mut state)
+    } else if let Some(token) = state
+        .bid_tokens
+        .iter()
+        .position(|bid_token| bid_token.asset.is_snip20(&env.message.sender))
+    {
+        let options: BidOptions = match msg.map(|msg| from_binary(&msg)).transpose() {
+            Ok(options) => options.unwrap_or_default(),
+            // send the tokens back if the msg can not be read as bid options
+            Err(_) => {
+                let message = String::from(
+                    "The msg could not be read as bid options. Bid tokens have been returned",
+                );
+
+                let resp = serde_json::to_string(&HandleAnswer::Bid {
+                    status: Failure,
+                    message,
+                    previous_bid: None,
+                    amount_bid: None,
+                    amount_returned: Some(amount),
+                    deposit: None,
+                    fee: None,
+                })
+                .unwrap();
+
+                return Ok(HandleResponse {
+                    messages: vec![state.bid_tokens[token].asset.transfer_msg(
+                        &state.auction_addr,
+                        from,
+                        amount,
+                    )?],
+                    log: vec![log("response", resp)],
+                    data: None,
+                });
+            }
+        };
+        try_bid(deps, env, from, amount, token, options, &mut state)
+    } else {
+        let message = format!(
+            "Address: {} is not a token in this auction",
+            env.message.sender
+        );
+        let resp = serde_json::to_string(&HandleAnswer::Status {
+            status: Failure,
+            message,
+        })
+        .unwrap();
+
+        Ok(HandleResponse {
+            messages: vec![],
+            log: vec![log("response", resp)],
+            data: None,
+        })
+    }
+}
+
+/// Returns HandleResult
+///
+/// process the ReceiveNft message sent after an NFT was sent to auction escrow
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `sender` - address that sent the NFT to escrow
+/// * `token_id` - ID of the NFT sent to escrow
+fn try_receive_nft<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    sender: HumanAddr,
+    token_id: String,
+) -> HandleResult {
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    if let Some(item) = state.lot.iter().position(|item| match &item.asset {
+        Asset::Snip721(nft) => {
+            nft.contract.address == env.message.sender && nft.token_id == token_id
+        }
+        _ => false,
+    }) {
+        return try_consign(deps, sender, Uint128(1), item, &mut state);
+    }
+    let from_nft_contract = state.lot.iter().find_map(|item| match &item.asset {
+        Asset::Snip721(nft) if nft.contract.address == env.message.sender => {
+            Some(nft.contract.clone())
+        }
+        _ => None,
+    });
+    let contract = if let Some(contract) = from_nft_contract {
+        contract
+    } else {
+        let message = format!(
+            "Address: {} is not a token in this auction",
+            env.message.sender
+        );
+        let resp = serde_json::to_string(&HandleAnswer::Status {
+            status: Failure,
+            message,
+        })
+        .unwrap();
+
+        return Ok(HandleResponse {
+            messages: vec![],
+            log: vec![log("response", resp)],
+            data: None,
+        });
+    };
+    // a different NFT from the same contract was sent, so send it back
+    let message = String::from("That NFT is not being sold in this auction.  It has been returned");
+
+    let resp = serde_json::to_string(&HandleAnswer::Consign {
+        status: Failure,
+        message,
+        amount_consigned: None,
+        amount_needed: None,
+        amount_returned: Some(Uint128(1)),
+    })
+    .unwrap();
+
+    let returned = Asset::Snip721(NftInfo { contract, token_id });
+    Ok(HandleResponse {
+        messages: vec![returned.transfer_msg(&state.auction_addr, sender, Uint128(1))?],
+        log: vec![log("response", resp)],
+        data: None,
+    })
+}
+
+/// Returns HandleResult
+///
+/// withdraw consigned tokens while there are no active bids
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `amount` - optional amount to withdraw.  Withdraws everything the caller consigned if None
+fn try_withdraw_consignment<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    amount: Option<Uint128>,
+) -> HandleResult {
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    let sender = env.message.sender;
+    if state.approvers.is_none() {
+        return withdraw_consignment(deps, state, sender, amount);
+    }
+    let (status, message) = match withdrawal_failure(&state, &sender, amount) {
+        Some(message) => (Failure, message),
+        None => {
+            let message = propose_change(
+                &mut state,
+                PendingChange::WithdrawConsignment {
+                    consignor: sender,
+                    amount,
+                },
+            );
+            save(&mut deps.storage, CONFIG_KEY, &state)?;
+            (Success, message)
+        }
+    };
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Consign {
+            status,
+            message,
+            amount_consigned: None,
+            amount_needed: None,
+            amount_returned: None,
+        })?),
+    })
+}
+
+/// Returns Option<String> of the reason a withdrawal of consigned tokens is not allowed, if any
+///
+/// # Arguments
+///
+/// * `state` - a reference to the auction State
+/// * `consignor` - address withdrawing the tokens
+/// * `amount` - optional amount to withdraw
+fn withdrawal_failure(
+    state: &State,
+    consignor: &HumanAddr,
+    amount: Option<Uint128>,
+) -> Option<String> {
+    let co_consigned = state
+        .co_consignments
+        .iter()
+        .any(|c| &c.consignor == consignor);
+    if consignor != &state.seller && !co_consigned {
+        Some(String::from(
+            "Only the auction creator or a co-seller can withdraw consigned tokens",
+        ))
+    } else if state.is_completed {
+        Some(String::from(
+            "Consignment can not be withdrawn after the auction has closed",
+        ))
+    } else if !state.bidders.is_empty() || !state.consortia.is_empty() {
+        Some(String::from(
+            "Consignment can not be withdrawn while there are active bids",
+        ))
+    } else if state.allowance_key.is_some() {
+        Some(String::from(
+            "This auction is consigned by allowance.  Lower the allowance instead",
+        ))
+    } else if amount.is_some() && state.lot.len() != 1 {
+        Some(String::from(
+            "An amount can only be withdrawn when selling a single token",
+        ))
+    } else {
+        None
+    }
+}
+
+/// Returns HandleResult
+///
+/// withdraw consigned tokens
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `state` - the auction State
+/// * `sender` - address withdrawing the tokens
+/// * `amount` - optional amount to withdraw.  Withdraws everything the sender consigned if None
+fn withdraw_consignment<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    mut state: State,
+    sender: HumanAddr,
+    amount: Option<Uint128>,
+) -> HandleResult {
+    if let Some(message) = withdrawal_failure(&state, &sender, amount) {
+        return Ok(HandleResponse {
+            messages: vec![],
+            log: vec![],
+            data: Some(to_binary(&HandleAnswer::Consign {
+                status: Failure,
+                message,
+                amount_consigned: None,
+                amount_needed: None,
+                amount_returned: None,
+            })?),
+        });
+    }
+    let co_consigned = state
+        .co_consignments
+        .iter()
+        .find(|c| c.consignor == sender)
+        .map(|c| c.amount);
+    let mut cos_msg = Vec::new();
+    let mut withdrawn = 0u128;
+    if let Some(co_consigned) = co_consigned {
+        // co-sellers withdraw from what they consigned
+        let requested = amount.map_or(co_consigned, |amount| amount.u128());
+        if requested > co_consigned {
+            return Ok(HandleResponse {
+                messages: vec![],
+                log: vec![],
+                data: Some(to_binary(&HandleAnswer::Consign {
+                    status: Failure,
+                    message: format!("You only have {} tokens consigned", co_consigned),
+                    amount_consigned: Some(Uint128(state.lot[0].consigned)),
+                    amount_needed: None,
+                    amount_returned: None,
+                })?),
+            });
+        }
+        state.co_consignments.retain(|c| c.consignor != sender);
+        if requested < co_consigned {
+            state.co_consignments.push(Consignment {
+                consignor: sender.clone(),
+                amount: co_consigned - requested,
+            });
+        }
+        if requested > 0 {
+            cos_msg.push(state.lot[0].asset.transfer_msg(
+                &state.auction_addr,
+                sender,
+                Uint128(requested),
+            )?);
+            state.lot[0].consigned -= requested;
+        }
+        withdrawn = requested;
+    } else {
+        // the seller can not withdraw what co-sellers consigned
+        let co_total: u128 = state.co_consignments.iter().map(|c| c.amount).sum();
+        let recipient = state.consignment_recipient();
+        for item in state.lot.iter_mut() {
+            let available = item.consigned - co_total;
+            let requested = amount.map_or(available, |amount| amount.u128());
+            if requested > available {
+                return Ok(HandleResponse {
+                    messages: vec![],
+                    log: vec![],
+                    data: Some(to_binary(&HandleAnswer::Consign {
+                        status: Failure,
+                        message: format!("You only have {} tokens consigned", available),
+                        amount_consigned: Some(Uint128(item.consigned)),
+                        amount_needed: None,
+                        amount_returned: None,
+                    })?),
+                });
+            }
+            if requested > 0 {
+                cos_msg.push(item.asset.transfer_msg(
+                    &state.auction_addr,
+                    recipient.clone(),
+                    Uint128(requested),
+                )?);
+                item.consigned -= requested;
+                withdrawn += requested;
+            }
+        }
+    }
+    if withdrawn == 0 {
+        return Ok(HandleResponse {
+            messages: vec![],
+            log: vec![],
+            data: Some(to_binary(&HandleAnswer::Consign {
+                status: Failure,
+                message: String::from("There are no consigned tokens to withdraw"),
+                amount_consigned: None,
+                amount_needed: None,
+                amount_returned: None,
+            })?),
+        });
+    }
+    state.tokens_consigned = state.lot.iter().all(|i| i.consigned >= i.amount);
+    // the approvals to finalize were given for the lot as it was consigned
+    state.clear_approvals();
+    save(&mut deps.storage, CONFIG_KEY, &state)?;
+
+    let mut message = String::from("Consigned tokens have been withdrawn");
+    if !state.tokens_consigned {
+        message.push_str(
+            ".  The full amount must be consigned again before the auction can close with a sale",
+        );
+    }
+    let single_item = state.lot.len() == 1;
+    Ok(HandleResponse {
+        messages: cos_msg,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Consign {
+            status: Success,
+            message,
+            // amounts of different tokens in a bundle can not be summed
+            amount_consigned: state
+                .lot
+                .first()
+                .filter(|_| single_item)
+                .map(|item| Uint128(item.consigned)),
+            amount_needed: state
+                .lot
+                .first()
+                .filter(|_| single_item)
+                .map(|item| Uint128(item.amount - item.consigned)),
+            amount_returned: Some(Uint128(withdrawn)).filter(|_| single_item),
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// verify the seller has granted the auction an allowance for the full sale amount
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `balance_key` - the seller's viewing key with the sell token
+fn try_consign_allowance<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    balance_key: String,
+) -> HandleResult {
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    let mut amount_consigned: Option<Uint128> = None;
+    let mut amount_needed: Option<Uint128> = None;
+    let status: ResponseStatus;
+    let message: String;
+    if env.message.sender != state.seller {
+        status = Failure;
+        message = String::from("Only auction creator can consign tokens for sale");
+    } else if state.is_completed {
+        status = Failure;
+        message = String::from("Auction has ended");
+    } else if state.is_paused {
+        status = Failure;
+        message = String::from("Auction is paused");
+    } else if state.allowance_key.is_some() {
+        state.seller_key = Some(balance_key);
+        let allowance = available_allowance(&deps.querier, &env, &state)?;
+        let amount = state.lot[0].amount;
+        if allowance < amount {
+            status = Failure;
+            message = String::from(
+                "The auction's allowance or your balance is less than the sale amount.  \
+                 Increase the allowance or balance and try again",
+            );
+            amount_consigned = Some(Uint128(allowance));
+            amount_needed = Some(Uint128(amount - allowance));
+            if state.tokens_consigned {
+                state.lot[0].consigned = 0;
+                state.tokens_consigned = false;
+                save(&mut deps.storage, CONFIG_KEY, &state)?;
+            }
+        } else {
+            status = Success;
+            message = String::from("Tokens to be sold are backed by an allowance");
+            amount_consigned = Some(Uint128(amount));
+            state.lot[0].consigned = amount;
+            state.tokens_consigned = true;
+            save(&mut deps.storage, CONFIG_KEY, &state)?;
+        }
+    } else {
+        status = Failure;
+        message = String::from("This auction is not consigned by allowance");
+    }
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Consign {
+            status,
+            message,
+            amount_consigned,
+            amount_needed,
+            amount_returned: None,
+        })?),
     })
 }
+
+/// Returns StdResult<u128> of the amount the auction can pull from the seller, which is the
+/// smaller of the seller's balance and the unexpired allowance the seller has granted the auction
+///
+/// # Arguments
+///
+/// * `querier` - a reference to the Querier dependency of the contract
+/// * `env` - a reference to the Env of contract's environment
+/// * `state` - a reference to the auction State
+fn available_allowance<Q: Querier>(querier: &Q, env: &Env, state: &State) -> StdResult<u128> {
+    let (info, key, balance_key) =
+        match (&state.lot[0].asset, &state.allowance_key, &state.seller_key) {
+            (Asset::Snip20(info), Some(key), Some(balance_key)) => (info, key, balance_key),
+            _ => return Ok(0),
+        };
+    let allowance = allowance_query(
+        querier,
+        state.seller.clone(),
+        state.auction_addr.clone(),
+        key.clone(),
+        BLOCK_SIZE,
+        info.code_hash.clone(),
+        info.address.clone(),
+    )?;
+    let allowance = match allowance.expiration {
+        Some(expiration) if expiration <= env.block.time => 0,
+        _ => allowance.allowance.u128(),
+    };
+    let balance = balance_query(
+        querier,
+        state.seller.clone(),
+        balance_key.clone(),
+        BLOCK_SIZE,
+        info.code_hash.clone(),
+        info.address.clone(),
+    )?;
+    Ok(allowance.min(balance.amount.u128()))
+}
+
+/// Returns HandleResult
+///
+/// process the Snip1155Receive message sent after SNIP-1155 tokens were sent to auction escrow
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `token_id` - ID of the tokens sent to escrow
+/// * `from` - address of owner of tokens sent to escrow
+/// * `amount` - Uint128 amount sent to escrow
+fn try_receive_snip1155<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    token_id: String,
+    from: HumanAddr,
+    amount: Uint128,
+) -> HandleResult {
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    if let Some(item) = state.lot.iter().position(|item| match &item.asset {
+        Asset::Snip1155(token) => {
+            token.contract.address == env.message.sender && token.token_id == token_id
+        }
+        _ => false,
+    }) {
+        return try_consign(deps, from, amount, item, &mut state);
+    }
+    let from_snip1155_contract = state.lot.iter().find_map(|item| match &item.asset {
+        Asset::Snip1155(token) if token.contract.address == env.message.sender => {
+            Some(token.contract.clone())
+        }
+        _ => None,
+    });
+    let contract = if let Some(contract) = from_snip1155_contract {
+        contract
+    } else {
+        let message = format!(
+            "Address: {} is not a token in this auction",
+            env.message.sender
+        );
+        let resp = serde_json::to_string(&HandleAnswer::Status {
+            status: Failure,
+            message,
+        })
+        .unwrap();
+
+        return Ok(HandleResponse {
+            messages: vec![],
+            log: vec![log("response", resp)],
+            data: None,
+        });
+    };
+    // a different token ID from the same contract was sent, so send it back
+    let message =
+        String::from("That token ID is not being sold in this auction.  It has been returned");
+
+    let resp = serde_json::to_string(&HandleAnswer::Consign {
+        status: Failure,
+        message,
+        amount_consigned: None,
+        amount_needed: None,
+        amount_returned: Some(amount),
+    })
+    .unwrap();
+
+    let returned = Asset::Snip1155(Snip1155Info { contract, token_id });
+    Ok(HandleResponse {
+        messages: vec![returned.transfer_msg(&state.auction_addr, from, amount)?],
+        log: vec![log("response", resp)],
+        data: None,
+    })
+}
+
+/// Returns HandleResult
+///
+/// process native SCRT sent to either place a bid or consign to auction escrow
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `consign` - true if the SCRT is being consigned, false if it is a bid
+/// * `options` - BidOptions of the bid
+fn try_native_deposit<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    consign: bool,
+    options: BidOptions,
+) -> HandleResult {
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    let native_item = state
+        .lot
+        .iter()
+        .position(|item| item.asset == Asset::Native);
+    let native_bid_token = state
+        .bid_tokens
+        .iter()
+        .position(|bid_token| bid_token.asset == Asset::Native);
+    let accepts_native = if consign {
+        native_item.is_some()
+    } else {
+        native_bid_token.is_some()
+    };
+    if !accepts_native {
+        let side = if consign { "sold" } else { "bid" };
+        return Err(StdError::generic_err(format!(
+            "SCRT is not the token being {} in this auction.  Your funds have been returned",
+            side
+        )));
+    }
+    let mut amount = 0u128;
+    for coin in &env.message.sent_funds {
+        if coin.denom != NATIVE_DENOM {
+            return Err(StdError::generic_err(format!(
+                "Only {} can be sent to this auction.  Your funds have been returned",
+                NATIVE_DENOM
+            )));
+        }
+        amount += coin.amount.u128();
+    }
+    let from = env.message.sender.clone();
+    match (native_item, native_bid_token) {
+        (Some(item), _) if consign => try_consign(deps, from, Uint128(amount), item, &mut state),
+        (_, Some(token)) => try_bid(deps, env, from, Uint128(amount), token, options, &mut state),
+        _ => Err(StdError::generic_err(
+            "SCRT is not accepted by this auction",
+        )),
+    }
+}
+
+/// Returns HandleResult
+///
+/// process the attempt to consign sale tokens to auction escrow
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `owner` - address of owner of tokens sent to escrow
+/// * `amount` - Uint128 amount sent to escrow
+/// * `item` - index of the lot item being consigned
+/// * `state` - mutable reference to auction state
+fn try_consign<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    owner: HumanAddr,
+    amount: Uint128,
+    item: usize,
+    state: &mut State,
+) -> HandleResult {
+    // if not the auction owner or a co-seller, send the tokens back
+    if owner != state.seller && !state.co_sellers.contains(&owner) {
+        let message = String::from(
+            "Only auction creator or an approved co-seller can consign tokens for sale.  Your \
+             tokens have been returned",
+        );
+
+        let resp = serde_json::to_string(&HandleAnswer::Consign {
+            status: Failure,
+            message,
+            amount_consigned: None,
+            amount_needed: None,
+            amount_returned: Some(amount),
+        })
+        .unwrap();
+
+        return Ok(HandleResponse {
+            messages: vec![state.lot[item].asset.transfer_msg(
+                &state.auction_addr,
+                owner,
+                amount,
+            )?],
+            log: vec![log("response", resp)],
+            data: None,
+        });
+    }
+    // if auction is over, send the tokens back
+    if state.is_completed {
+        let message = String::from("Auction has ended. Your tokens have been returned");
+
+        let resp = serde_json::to_string(&HandleAnswer::Consign {
+            status: Failure,
+            message,
+            amount_consigned: None,
+            amount_needed: None,
+            amount_returned: Some(amount),
+        })
+        .unwrap();
+
+        return Ok(HandleResponse {
+            messages: vec![state.lot[item].asset.transfer_msg(
+                &state.auction_addr,
+                owner,
+                amount,
+            )?],
+            log: vec![log("response", resp)],
+            data: None,
+        });
+    }
+    // if the auction is paused, send the tokens back
+    if state.is_paused {
+        let message = String::from("Auction is paused. Your tokens have been returned");
+
+        let resp = serde_json::to_string(&HandleAnswer::Consign {
+            status: Failure,
+            message,
+            amount_consigned: None,
+            amount_needed: None,
+            amount_returned: Some(amount),
+        })
+        .unwrap();
+
+        return Ok(HandleResponse {
+            messages: vec![state.lot[item].asset.transfer_msg(
+                &state.auction_addr,
+                owner,
+                amount,
+            )?],
+            log: vec![log("response", resp)],
+            data: None,
+        });
+    }
+    // if the seller is granting an allowance, return these tokens
+    if state.allowance_key.is_some() {
+        let message =
+            String::from("This auction is consigned by allowance.  Your tokens have been returned");
+
+        let resp = serde_json::to_string(&HandleAnswer::Consign {
+            status: Failure,
+            message,
+            amount_consigned: None,
+            amount_needed: None,
+            amount_returned: Some(amount),
+        })
+        .unwrap();
+
+        return Ok(HandleResponse {
+            messages: vec![state.lot[item].asset.transfer_msg(
+                &state.auction_addr,
+                owner,
+                amount,
+            )?],
+            log: vec![log("response", resp)],
+            data: None,
+        });
+    }
+    // if tokens to be sold have already been consigned, return these tokens
+    if state.lot[item].consigned >= state.lot[item].amount {
+        let message = String::from(
+            "Tokens to be sold have already been consigned. Your tokens have been returned",
+        );
+
+        let resp = serde_json::to_string(&HandleAnswer::Consign {
+            status: Failure,
+            message,
+            amount_consigned: Some(Uint128(state.lot[item].consigned)),
+            amount_needed: None,
+            amount_returned: Some(amount),
+        })
+        .unwrap();
+
+        return Ok(HandleResponse {
+            messages: vec![state.lot[item].asset.transfer_msg(
+                &state.auction_addr,
+                owner,
+                amount,
+            )?],
+            log: vec![log("response", resp)],
+            data: None,
+        });
+    }
+
+    // record what each co-seller consigned so proceeds and returns can be split pro-rata
+    if owner != state.seller {
+        let accepted = amount
+            .u128()
+            .min(state.lot[item].amount - state.lot[item].consigned);
+        match state
+            .co_consignments
+            .iter_mut()
+            .find(|c| c.consignor == owner)
+        {
+            Some(consignment) => consignment.amount += accepted,
+            None => state.co_consignments.push(Consignment {
+                consignor: owner.clone(),
+                amount: accepted,
+            }),
+        }
+    }
+    let lot_item = &mut state.lot[item];
+    let consign_total = lot_item.consigned + amount.u128();
+    let mut log_msg = String::new();
+    let mut cos_msg = Vec::new();
+    let status: ResponseStatus;
+    let mut excess: Option<Uint128> = None;
+    let mut needed: Option<Uint128> = None;
+    // if consignment amount < auction sell amount, ask for remaining balance
+    if consign_total < lot_item.amount {
+        lot_item.consigned = consign_total;
+        needed = Some(Uint128(lot_item.amount - consign_total));
+        status = Failure;
+        log_msg.push_str(
+            "You have not consigned the full amount to be sold.  You need to consign additional \
+             tokens",
+        );
+    // all tokens to be sold have been consigned
+    } else {
+        lot_item.consigned = lot_item.amount;
+        // if consigned more than needed, return excess tokens
+        if consign_total > lot_item.amount {
+            excess = Some(Uint128(consign_total - lot_item.amount));
+            cos_msg.push(lot_item.asset.transfer_msg(
+                &state.auction_addr,
+                owner,
+                Uint128(consign_total - lot_item.amount),
+            )?);
+        }
+        state.tokens_consigned = state.lot.iter().all(|i| i.consigned >= i.amount);
+        status = Success;
+        if state.tokens_consigned {
+            log_msg.push_str("Tokens to be sold have been consigned to the auction");
+        } else {
+            log_msg.push_str(
+                "These tokens have been consigned.  Other assets in the lot still need to be \
+                 consigned",
+            );
+        }
+        if excess.is_some() {
+            log_msg.push_str(".  Excess tokens have been returned");
+        }
+    }
+
+    save(&mut deps.storage, CONFIG_KEY, &state)?;
+
+    let resp = serde_json::to_string(&HandleAnswer::Consign {
+        status,
+        message: log_msg,
+        amount_consigned: Some(Uint128(state.lot[item].consigned)),
+        amount_needed: needed,
+        amount_returned: excess,
+    })
+    .unwrap();
+
+    Ok(HandleResponse {
+        messages: cos_msg,
+        log: vec![log("response", resp)],
+        data: None,
+    })
+}
+
+/// Returns HandleResult
+///
+/// process the bid attempt
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `bidder` - address of owner of tokens sent to escrow
+/// * `amount` - Uint128 amount sent to escrow
+/// * `token` - index of the bid token sent to escrow
+/// * `options` - BidOptions sent with the bid
+/// * `state` - mutable reference to auction state
+fn try_bid<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    bidder: HumanAddr,
+    amount: Uint128,
+    token: usize,
+    options: BidOptions,
+    state: &mut State,
+) -> HandleResult {
+    // the winner of a deposit-backed bid is paying the balance
+    if let Some(pending) = &state.awaiting_payment {
+        if pending.winner == bidder && pending.bid.token == token {
+            return try_pay_balance(deps, env, amount, state);
+        }
+    }
+    // if auction is over, send the tokens back
+    if state.is_completed {
+        let message = String::from("Auction has ended. Bid tokens have been returned");
+
+        let resp = serde_json::to_string(&HandleAnswer::Bid {
+            status: Failure,
+            message,
+            previous_bid: None,
+            amount_bid: None,
+            amount_returned: Some(amount),
+            deposit: None,
+            fee: None,
+        })
+        .unwrap();
+
+        return Ok(HandleResponse {
+            messages: vec![state.bid_tokens[token].asset.transfer_msg(
+                &state.auction_addr,
+                bidder,
+                amount,
+            )?],
+            log: vec![log("response", resp)],
+            data: None,
+        });
+    }
+    // if the auction is paused, send the tokens back
+    if state.is_paused {
+        let message = String::from("Auction is paused. Bid tokens have been returned");
+
+        let resp = serde_json::to_string(&HandleAnswer::Bid {
+            status: Failure,
+            message,
+            previous_bid: None,
+            amount_bid: None,
+            amount_returned: Some(amount),
+            deposit: None,
+            fee: None,
+        })
+        .unwrap();
+
+        return Ok(HandleResponse {
+            messages: vec![state.bid_tokens[token].asset.transfer_msg(
+                &state.auction_addr,
+                bidder,
+                amount,
+            )?],
+            log: vec![log("response", resp)],
+            data: None,
+        });
+    }
+    // don't accept a 0 bid
+    if amount == Uint128(0) {
+        let message = String::from("Bid must be greater than 0");
+
+        let resp = serde_json::to_string(&HandleAnswer::Bid {
+            status: Failure,
+            message,
+            previous_bid: None,
+            amount_bid: None,
+            amount_returned: None,
+            deposit: None,
+            fee: None,
+        })
+        .unwrap();
+
+        return Ok(HandleResponse {
+            messages: vec![],
+            log: vec![log("response", resp)],
+            data: None,
+        });
+    }
+    // contributions to a consortium are pooled into one shared bid
+    if let Some(consortium) = options.consortium {
+        return try_contribute(deps, env, bidder, amount, token, consortium, state);
+    }
+    let bidder_raw = &deps.api.canonical_address(&bidder)?;
+    // the placement fee is kept from the first deposit of a bidder without an active bid
+    let placement_fee = match &state.bid_fee {
+        Some(bid_fee) if !state.bidders.contains(bidder_raw.as_slice()) => {
+            state.bid_tokens[token].amount_worth(bid_fee.amount.u128())?
+        }
+        _ => 0,
+    };
+    if amount.u128() <= placement_fee {
+        let message = format!(
+            "Bid must be greater than the placement fee of {}.  Bid tokens have been returned",
+            placement_fee
+        );
+
+        let resp = serde_json::to_string(&HandleAnswer::Bid {
+            status: Failure,
+            message,
+            previous_bid: None,
+            amount_bid: None,
+            amount_returned: Some(amount),
+            deposit: None,
+            fee: None,
+        })
+        .unwrap();
+
+        return Ok(HandleResponse {
+            messages: vec![state.bid_tokens[token].asset.transfer_msg(
+                &state.auction_addr,
+                bidder,
+                amount,
+            )?],
+            log: vec![log("response", resp)],
+            data: None,
+        });
+    }
+    let escrowed = amount.u128() - placement_fee;
+    // with deposit-backed bids, the tokens sent only need to cover the deposit of the full bid
+    let bid_amount = match (&state.deposit, options.bid_amount) {
+        (Some(_), Some(bid_amount)) => bid_amount.u128(),
+        _ => escrowed,
+    };
+    if let Some(terms) = &state.deposit {
+        let required = bid_amount
+            .checked_mul(terms.rate_bps as u128)
+            .map(|product| product / 10000 + (product % 10000 != 0) as u128)
+            .ok_or_else(|| StdError::generic_err("Bid is too large"))?;
+        if escrowed < required || escrowed > bid_amount {
+            let message = format!(
+                "Deposit must be at least {} and no more than the bid.  Bid tokens have been \
+                 returned",
+                required
+            );
+
+            let resp = serde_json::to_string(&HandleAnswer::Bid {
+                status: Failure,
+                message,
+                previous_bid: None,
+                amount_bid: None,
+                amount_returned: Some(amount),
+                deposit: None,
+                fee: None,
+            })
+            .unwrap();
+
+            return Ok(HandleResponse {
+                messages: vec![state.bid_tokens[token].asset.transfer_msg(
+                    &state.auction_addr,
+                    bidder,
+                    amount,
+                )?],
+                log: vec![log("response", resp)],
+                data: None,
+            });
+        }
+    }
+    let value = state.bid_tokens[token].value(bid_amount)?;
+    // if bid is less than the minimum accepted bid, send the tokens back
+    if value < state.minimum_bid {
+        let message =
+            String::from("Bid was less than minimum allowed.  Bid tokens have been returned");
+
+        let resp = serde_json::to_string(&HandleAnswer::Bid {
+            status: Failure,
+            message,
+            previous_bid: None,
+            amount_bid: None,
+            amount_returned: Some(amount),
+            deposit: None,
+            fee: None,
+        })
+        .unwrap();
+
+        return Ok(HandleResponse {
+            messages: vec![state.bid_tokens[token].asset.transfer_msg(
+                &state.auction_addr,
+                bidder,
+                amount,
+            )?],
+            log: vec![log("response", resp)],
+            data: None,
+        });
+    }
+    let mut return_amount: Option<Uint128> = None;
+    let mut return_token = token;
+
+    // if there is an active bid from this address
+    if state.bidders.contains(&bidder_raw.as_slice().to_vec()) {
+        let bid: Option<Bid> = may_load(&deps.storage, bidder_raw.as_slice())?;
+        if let Some(old_bid) = bid {
+            // if new bid is <= the old bid, keep old bid and return this one
+            if value <= old_bid.value {
+                let message = String::from(
+                    "New bid less than or equal to previous bid. Newly bid tokens have been \
+                     returned",
+                );
+
+                let resp = serde_json::to_string(&HandleAnswer::Bid {
+                    status: Failure,
+                    message,
+                    previous_bid: Some(Uint128(old_bid.amount)),
+                    amount_bid: None,
+                    amount_returned: Some(amount),
+                    deposit: None,
+                    fee: None,
+                })
+                .unwrap();
+
+                return Ok(HandleResponse {
+                    messages: vec![state.bid_tokens[token].asset.transfer_msg(
+                        &state.auction_addr,
+                        bidder,
+                        amount,
+                    )?],
+                    log: vec![log("response", resp)],
+                    data: None,
+                });
+            // new bid is larger, save the new bid, and return the old one, so mark for return
+            } else {
+                return_amount = Some(Uint128(old_bid.escrowed()));
+                return_token = old_bid.token;
+            }
+        }
+    // address did not have an active bid
+    } else {
+        // insert in list of bidders
+        state.bidders.insert(bidder_raw.as_slice().to_vec());
+    }
+    state.clear_approvals();
+    save(&mut deps.storage, CONFIG_KEY, &state)?;
+    let deposit = state.deposit.as_ref().map(|_| escrowed);
+    // only referrers the seller listed can earn a reward
+    let referrer = match options.referrer {
+        Some(referrer) => Some(deps.api.canonical_address(&referrer)?)
+            .filter(|raw| state.referral_rate_bps.is_some() && state.referrers.contains(raw)),
+        None => None,
+    };
+    let new_bid = Bid {
+        amount: bid_amount,
+        timestamp: env.block.time,
+        token,
+        value,
+        payout: validate_callback(&deps.api, options.payout)?,
+        deposit,
+        referrer,
+    };
+    save(&mut deps.storage, bidder_raw.as_slice(), &new_bid)?;
+
+    let mut message = String::from("Bid accepted");
+    let mut cos_msg = Vec::new();
+
+    // if need to return the old bid
+    if let Some(returned) = return_amount {
+        cos_msg.push(state.bid_tokens[return_token].asset.transfer_msg(
+            &state.auction_addr,
+            bidder,
+            returned,
+        )?);
+        message.push_str(". Previously bid tokens have been returned");
+    }
+    let mut fee: Option<Uint128> = None;
+    if let Some(bid_fee) = state.bid_fee.as_ref().filter(|_| placement_fee > 0) {
+        cos_msg.push(state.bid_tokens[token].asset.transfer_msg(
+            &state.auction_addr,
+            state.bid_fee_recipient(bid_fee.to_fee_collector),
+            Uint128(placement_fee),
+        )?);
+        fee = Some(Uint128(placement_fee));
+        message.push_str(&format!(". A placement fee of {} was kept", placement_fee));
+    }
+    let resp = serde_json::to_string(&HandleAnswer::Bid {
+        status: Success,
+        message,
+        previous_bid: None,
+        amount_bid: Some(Uint128(bid_amount)),
+        amount_returned: return_amount,
+        deposit: deposit.map(Uint128),
+        fee,
+    })
+    .unwrap();
+
+    Ok(HandleResponse {
+        messages: cos_msg,
+        log: vec![log("response", resp)],
+        data: None,
+    })
+}
+
+/// Returns Vec<u8> of the storage key of a consortium bid
+///
+/// # Arguments
+///
+/// * `consortium` - id of the consortium
+fn consortium_key(consortium: &str) -> Vec<u8> {
+    [CONSORTIUM_PREFIX, consortium.as_bytes()].concat()
+}
+
+/// Returns HandleResult
+///
+/// process a contribution to a consortium bid
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `contributor` - address of the contributor
+/// * `amount` - Uint128 amount sent to escrow
+/// * `token` - index of the bid token sent
+/// * `consortium` - id of the consortium
+/// * `state` - mutable reference to auction state
+fn try_contribute<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    contributor: HumanAddr,
+    amount: Uint128,
+    token: usize,
+    consortium: String,
+    state: &mut State,
+) -> HandleResult {
+    let key = consortium_key(&consortium);
+    let existing: Option<Consortium> = may_load(&deps.storage, &key)?;
+    // the placement fee is kept from a contributor's first contribution to the consortium
+    let placement_fee = match (&state.bid_fee, &existing) {
+        (Some(_), Some(pool))
+            if pool
+                .contributions
+                .iter()
+                .any(|c| c.contributor == contributor) =>
+        {
+            0
+        }
+        (Some(bid_fee), _) => state.bid_tokens[token].amount_worth(bid_fee.amount.u128())?,
+        (None, _) => 0,
+    };
+    let failure = if !state.consortium_bids {
+        Some(String::from(
+            "This auction does not accept consortium bids.  Bid tokens have been returned",
+        ))
+    } else if matches!(&existing, Some(pool) if pool.bid.token != token) {
+        Some(String::from(
+            "Contributions to a consortium must all be made in the same token.  Bid tokens have \
+             been returned",
+        ))
+    } else if amount.u128() <= placement_fee {
+        Some(format!(
+            "Contribution must be greater than the placement fee of {}.  Bid tokens have been \
+             returned",
+            placement_fee
+        ))
+    } else {
+        None
+    };
+    if let Some(message) = failure {
+        let resp = serde_json::to_string(&HandleAnswer::Bid {
+            status: Failure,
+            message,
+            previous_bid: None,
+            amount_bid: None,
+            amount_returned: Some(amount),
+            deposit: None,
+            fee: None,
+        })
+        .unwrap();
+
+        return Ok(HandleResponse {
+            messages: vec![state.bid_tokens[token].asset.transfer_msg(
+                &state.auction_addr,
+                contributor,
+                amount,
+            )?],
+            log: vec![log("response", resp)],
+            data: None,
+        });
+    }
+    let contributed = amount.u128() - placement_fee;
+    let mut pool = existing.unwrap_or_else(|| Consortium {
+        bid: Bid {
+            amount: 0,
+            timestamp: env.block.time,
+            token,
+            value: 0,
+            payout: None,
+            deposit: None,
+            referrer: None,
+        },
+        contributions: Vec::new(),
+    });
+    pool.bid.amount = pool
+        .bid
+        .amount
+        .checked_add(contributed)
+        .ok_or_else(|| StdError::generic_err("Consortium bid is too large"))?;
+    pool.bid.value = state.bid_tokens[token].value(pool.bid.amount)?;
+    // a larger shared bid counts as a new bid when breaking ties
+    pool.bid.timestamp = env.block.time;
+    match pool
+        .contributions
+        .iter_mut()
+        .find(|c| c.contributor == contributor)
+    {
+        Some(existing) => existing.amount += contributed,
+        None => pool.contributions.push(Contribution {
+            contributor,
+            amount: contributed,
+        }),
+    }
+    save(&mut deps.storage, &key, &pool)?;
+    state.consortia.insert(consortium);
+    state.clear_approvals();
+    save(&mut deps.storage, CONFIG_KEY, &state)?;
+
+    let mut message = String::from("Contribution accepted");
+    if pool.bid.value < state.minimum_bid {
+        message.push_str(".  The consortium's bid is still less than the minimum bid");
+    }
+    let mut cos_msg = Vec::new();
+    let mut fee: Option<Uint128> = None;
+    if let Some(bid_fee) = state.bid_fee.as_ref().filter(|_| placement_fee > 0) {
+        cos_msg.push(state.bid_tokens[token].asset.transfer_msg(
+            &state.auction_addr,
+            state.bid_fee_recipient(bid_fee.to_fee_collector),
+            Uint128(placement_fee),
+        )?);
+        fee = Some(Uint128(placement_fee));
+        message.push_str(&format!(".  A placement fee of {} was kept", placement_fee));
+    }
+    let resp = serde_json::to_string(&HandleAnswer::Bid {
+        status: Success,
+        message,
+        previous_bid: None,
+        amount_bid: Some(Uint128(pool.bid.amount)),
+        amount_returned: None,
+        deposit: None,
+        fee,
+    })
+    .unwrap();
+
+    Ok(HandleResponse {
+        messages: cos_msg,
+        log: vec![log("response", resp)],
+        data: None,
+    })
+}
+
+/// Returns HandleResult
+///
+/// attempt to retract current bid
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+fn try_retract<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> HandleResult {
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    let bidder = env.message.sender;
+    let bidder_raw = &deps.api.canonical_address(&bidder)?;
+    let mut cos_msg = Vec::new();
+    let sent: Option<Uint128>;
+    let mut fee: Option<Uint128> = None;
+    let mut log_msg = String::new();
+    let status: ResponseStatus;
+    // bids are locked in for the period before the scheduled end
+    if state.retraction_locked(env.block.time) {
+        status = Failure;
+        sent = None;
+        log_msg.push_str("Bids can not be retracted this close to the end of the auction");
+    // if there was a active bid from this address, remove the bid and return tokens
+    } else if state.bidders.contains(&bidder_raw.as_slice().to_vec()) {
+        let bid: Option<Bid> = may_load(&deps.storage, bidder_raw.as_slice())?;
+        if let Some(old_bid) = bid {
+            remove(&mut deps.storage, bidder_raw.as_slice());
+            state.bidders.remove(&bidder_raw.as_slice().to_vec());
+            state.clear_approvals();
+            save(&mut deps.storage, CONFIG_KEY, &state)?;
+            let escrowed = old_bid.escrowed();
+            let kept = state.retraction_fee(escrowed)?;
+            let asset = &state.bid_tokens[old_bid.token].asset;
+            if kept > 0 {
+                let to_fee_collector = matches!(
+                    &state.retraction,
+                    Some(terms) if terms.to_fee_collector
+                );
+                cos_msg.push(asset.transfer_msg(
+                    &state.auction_addr,
+                    state.bid_fee_recipient(to_fee_collector),
+                    Uint128(kept),
+                )?);
+                fee = Some(Uint128(kept));
+            }
+            if escrowed > kept {
+                cos_msg.push(asset.transfer_msg(
+                    &state.auction_addr,
+                    bidder,
+                    Uint128(escrowed - kept),
+                )?);
+            }
+            status = Success;
+            sent = Some(Uint128(escrowed - kept));
+            if kept > 0 {
+                log_msg.push_str(
+                    "Bid retracted.  Tokens have been returned after keeping the retraction fee",
+                );
+            } else {
+                log_msg.push_str("Bid retracted.  Tokens have been returned");
+            }
+        } else {
+            status = Failure;
+            sent = None;
+            log_msg.push_str(&format!("No active bid for address: {}", bidder));
+        }
+    // no active bid found
+    } else {
+        status = Failure;
+        sent = None;
+        log_msg.push_str(&format!("No active bid for address: {}", bidder));
+    }
+    Ok(HandleResponse {
+        messages: cos_msg,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RetractBid {
+            status,
+            message: log_msg,
+            amount_returned: sent,
+            fee,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// attempt to retract a contribution to a consortium bid
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `consortium` - id of the consortium
+fn try_retract_contribution<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    consortium: String,
+) -> HandleResult {
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    let contributor = env.message.sender;
+    let key = consortium_key(&consortium);
+    let mut cos_msg = Vec::new();
+    let mut sent: Option<Uint128> = None;
+    let mut fee: Option<Uint128> = None;
+    let status: ResponseStatus;
+    let message: String;
+    // settled and refunded consortia are no longer active
+    let pool: Option<Consortium> = if state.consortia.contains(&consortium) {
+        may_load(&deps.storage, &key)?
+    } else {
+        None
+    };
+    let found = pool.and_then(|pool| {
+        let index = pool
+            .contributions
+            .iter()
+            .position(|c| c.contributor == contributor)?;
+        Some((pool, index))
+    });
+    // contributions are locked in for the period before the scheduled end, the same as bids
+    if state.retraction_locked(env.block.time) {
+        status = Failure;
+        message = String::from("Bids can not be retracted this close to the end of the auction");
+    } else if let Some((mut pool, index)) = found {
+        let contribution = pool.contributions.remove(index);
+        if pool.contributions.is_empty() {
+            remove(&mut deps.storage, &key);
+            state.consortia.remove(&consortium);
+        } else {
+            pool.bid.amount -= contribution.amount;
+            pool.bid.value = state.bid_tokens[pool.bid.token].value(pool.bid.amount)?;
+            save(&mut deps.storage, &key, &pool)?;
+        }
+        state.clear_approvals();
+        save(&mut deps.storage, CONFIG_KEY, &state)?;
+        let kept = state.retraction_fee(contribution.amount)?;
+        let asset = &state.bid_tokens[pool.bid.token].asset;
+        if kept > 0 {
+            let to_fee_collector = matches!(
+                &state.retraction,
+                Some(terms) if terms.to_fee_collector
+            );
+            cos_msg.push(asset.transfer_msg(
+                &state.auction_addr,
+                state.bid_fee_recipient(to_fee_collector),
+                Uint128(kept),
+            )?);
+            fee = Some(Uint128(kept));
+        }
+        if contribution.amount > kept {
+            cos_msg.push(asset.transfer_msg(
+                &state.auction_addr,
+                contributor,
+                Uint128(contribution.amount - kept),
+            )?);
+        }
+        status = Success;
+        sent = Some(Uint128(contribution.amount - kept));
+        message = if kept > 0 {
+            String::from(
+                "Contribution retracted.  Tokens have been returned after keeping the retraction \
+                 fee",
+            )
+        } else {
+            String::from("Contribution retracted.  Tokens have been returned")
+        };
+    } else {
+        status = Failure;
+        message = format!(
+            "No active contribution from address {} to consortium {}",
+            contributor, consortium
+        );
+    }
+    Ok(HandleResponse {
+        messages: cos_msg,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RetractBid {
+            status,
+            message,
+            amount_returned: sent,
+            fee,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// sends the winner the won tokens waiting for a payout callback, or the sold tokens that have
+/// unlocked and not yet been claimed
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `plain_transfer` - true if the tokens should be transferred without the payout callback
+fn try_claim<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    plain_transfer: bool,
+) -> HandleResult {
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    let mut cos_msg = Vec::new();
+    let mut amount_claimed: Option<Uint128> = None;
+    let status: ResponseStatus;
+    let message: String;
+    let callback = state.winner_payout.as_ref().filter(|_| !plain_transfer);
+    if state.winner.as_ref() != Some(&env.message.sender) {
+        status = Failure;
+        message = String::from("Only the winner can claim tokens");
+    } else if state.payout_unclaimed {
+        for item in &state.lot {
+            if let Asset::Snip20(_) = item.asset {
+                cos_msg.push(item.asset.payout_msg(
+                    &state.auction_addr,
+                    env.message.sender.clone(),
+                    Uint128(item.amount),
+                    callback,
+                )?);
+            }
+        }
+        // amounts of different tokens in a bundle can not be summed
+        if state.lot.len() == 1 {
+            amount_claimed = Some(Uint128(state.lot[0].amount));
+        }
+        state.payout_unclaimed = false;
+        save(&mut deps.storage, CONFIG_KEY, &state)?;
+        status = Success;
+        message = String::from("Won tokens have been sent");
+    } else if state.vesting_start.is_none() {
+        status = Failure;
+        message = String::from("There are no tokens to claim");
+    } else {
+        let claimable = state.vested(env.block.time)? - state.vesting_claimed;
+        if claimable == 0 {
+            status = Failure;
+            message = String::from("There are no unlocked tokens to claim");
+        } else {
+            cos_msg.push(state.lot[0].asset.payout_msg(
+                &state.auction_addr,
+                env.message.sender,
+                Uint128(claimable),
+                callback,
+            )?);
+            state.vesting_claimed += claimable;
+            save(&mut deps.storage, CONFIG_KEY, &state)?;
+            status = Success;
+            message = String::from("Unlocked tokens have been sent");
+            amount_claimed = Some(Uint128(claimable));
+        }
+    }
+    Ok(HandleResponse {
+        messages: cos_msg,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Claim {
+            status,
+            message,
+            amount_claimed,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// sends the seller the streamed proceeds that have accrued and not yet been claimed
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `plain_transfer` - true if the proceeds should be sent without the proceeds callback
+fn try_claim_proceeds<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    plain_transfer: bool,
+) -> HandleResult {
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    let mut cos_msg = Vec::new();
+    let mut amount_claimed: Option<Uint128> = None;
+    let status: ResponseStatus;
+    let message: String;
+    if env.message.sender != state.seller {
+        status = Failure;
+        message = String::from("Only the auction creator can claim the proceeds");
+    } else if state.stream_start.is_none() {
+        status = Failure;
+        message = String::from("There are no streamed proceeds to claim");
+    } else {
+        let claimable = state.streamed(env.block.time)? - state.stream_claimed;
+        if claimable == 0 {
+            status = Failure;
+            message = String::from("No proceeds have accrued since the last claim");
+        } else {
+            cos_msg =
+                distribute_proceeds(&state, state.winning_bid_token, claimable, plain_transfer)?;
+            state.stream_claimed += claimable;
+            save(&mut deps.storage, CONFIG_KEY, &state)?;
+            status = Success;
+            message = String::from("Accrued proceeds have been sent");
+            amount_claimed = Some(Uint128(claimable));
+        }
+    }
+    Ok(HandleResponse {
+        messages: cos_msg,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Claim {
+            status,
+            message,
+            amount_claimed,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// process the balance paid for a winning deposit-backed bid
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `amount` - Uint128 amount sent to escrow
+/// * `state` - mutable reference to auction state
+fn try_pay_balance<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    amount: Uint128,
+    state: &mut State,
+) -> HandleResult {
+    let mut pending = match state.awaiting_payment.take() {
+        Some(pending) => pending,
+        None => return Err(StdError::generic_err("No payment is due")),
+    };
+    let owed = pending.bid.amount - pending.bid.escrowed();
+    let failure = if env.block.time >= pending.deadline {
+        Some(String::from(
+            "The payment deadline has passed.  Your tokens have been returned",
+        ))
+    } else if amount.u128() < owed {
+        Some(format!(
+            "The balance of your bid is {}.  Your tokens have been returned",
+            owed
+        ))
+    } else {
+        None
+    };
+    let asset = state.bid_tokens[pending.bid.token].asset.clone();
+    if let Some(message) = failure {
+        let resp = serde_json::to_string(&HandleAnswer::CloseAuction {
+            status: Failure,
+            message,
+            winning_bid: None,
+            amount_returned: Some(amount),
+            fee: None,
+        })
+        .unwrap();
+
+        return Ok(HandleResponse {
+            messages: vec![asset.transfer_msg(&state.auction_addr, pending.winner, amount)?],
+            log: vec![log("response", resp)],
+            data: None,
+        });
+    }
+    let winner = pending.winner.clone();
+    let winning_bid = pending.bid.amount;
+    pending.bid.deposit = None;
+    let (mut cos_msg, release_time) = close_sale(
+        &deps.api,
+        state,
+        pending.winner,
+        pending.bid,
+        env.block.time,
+    )?;
+    // the backup bids are no longer needed
+    cos_msg.extend(refund_bids(deps, state)?);
+    // return any overpayment
+    let excess = amount.u128() - owed;
+    let mut amount_returned: Option<Uint128> = None;
+    if excess > 0 {
+        cos_msg.push(asset.transfer_msg(&state.auction_addr, winner, Uint128(excess))?);
+        amount_returned = Some(Uint128(excess));
+    }
+    save(&mut deps.storage, CONFIG_KEY, &state)?;
+
+    let message = if let Some(release_time) = release_time {
+        format!(
+            "Balance paid.  The sale will be held until {} before it can be released",
+            release_time
+        )
+    } else {
+        String::from("Balance paid.  Sale completed")
+    };
+    let resp = serde_json::to_string(&HandleAnswer::CloseAuction {
+        status: Success,
+        message,
+        winning_bid: Some(Uint128(winning_bid)),
+        amount_returned,
+        fee: state
+            .fee
+            .as_ref()
+            .filter(|_| release_time.is_none())
+            .map(|_| Uint128(state.fee_paid)),
+    })
+    .unwrap();
+
+    Ok(HandleResponse {
+        messages: cos_msg,
+        log: vec![log("response", resp)],
+        data: None,
+    })
+}
+
+/// Returns HandleResult
+///
+/// forfeit the deposit of a winner who did not pay the balance by the deadline, and offer the lot
+/// to the next highest bidder
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+fn try_declare_default<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> HandleResult {
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    let failure = match &state.awaiting_payment {
+        None => Some("No winner is waiting to pay the balance of their bid"),
+        Some(pending) if env.block.time < pending.deadline => {
+            Some("The payment deadline has not passed")
+        }
+        _ => None,
+    };
+    if let Some(message) = failure {
+        return Ok(HandleResponse {
+            messages: vec![],
+            log: vec![],
+            data: Some(to_binary(&HandleAnswer::CloseAuction {
+                status: Failure,
+                message: String::from(message),
+                winning_bid: None,
+                amount_returned: None,
+                fee: None,
+            })?),
+        });
+    }
+    let mut cos_msg = Vec::new();
+    // the defaulted deposit is shared out like proceeds, but it is paid at once even if the
+    // proceeds are streamed
+    if let Some(defaulted) = state.awaiting_payment.take() {
+        let token = defaulted.bid.token;
+        let (messages, _, proceeds) =
+            split_proceeds(&mut state, token, defaulted.bid.escrowed(), None)?;
+        cos_msg.extend(messages);
+        cos_msg.extend(distribute_proceeds(&state, token, proceeds, false)?);
+    }
+    // find the next highest bid, with earlier bids winning ties
+    let mut next: Option<(Vec<u8>, Bid)> = None;
+    for bidder in state.bidders.iter() {
+        let bid: Option<Bid> = may_load(&deps.storage, bidder)?;
+        if let Some(bid) = bid {
+            let better = match &next {
+                Some((_, best)) => {
+                    bid.value > best.value
+                        || (bid.value == best.value && bid.timestamp < best.timestamp)
+                }
+                None => true,
+            };
+            if better {
+                next = Some((bidder.clone(), bid));
+            }
+        }
+    }
+    let message: String;
+    let mut winning_bid: Option<Uint128> = None;
+    if let Some((bidder_raw, bid)) = next {
+        remove(&mut deps.storage, &bidder_raw);
+        state.bidders.remove(&bidder_raw);
+        let winner = deps
+            .api
+            .human_address(&CanonicalAddr::from(bidder_raw.as_slice()))?;
+        winning_bid = Some(Uint128(bid.amount));
+        if let (Some(terms), true) = (&state.deposit, bid.escrowed() < bid.amount) {
+            let deadline = deadline_after(env.block.time, terms.payment_window)?;
+            state.awaiting_payment = Some(PendingPayment {
+                winner,
+                bid,
+                deadline,
+            });
+            message = format!(
+                "Deposit forfeited.  The next highest bidder must pay the balance of their bid \
+                 by {}",
+                deadline
+            );
+        } else {
+            let (messages, _) = close_sale(&deps.api, &mut state, winner, bid, env.block.time)?;
+            cos_msg.extend(messages);
+            cos_msg.extend(refund_bids(deps, &mut state)?);
+            message = String::from(
+                "Deposit forfeited.  The lot has been sold to the next highest bidder",
+            );
+        }
+    } else {
+        cos_msg.extend(return_consignment(&mut state)?);
+        message = String::from(
+            "Deposit forfeited.  No other bids remain, so the consigned tokens have been returned",
+        );
+    }
+    save(&mut deps.storage, CONFIG_KEY, &state)?;
+
+    Ok(HandleResponse {
+        messages: cos_msg,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::CloseAuction {
+            status: Success,
+            message,
+            winning_bid,
+            amount_returned: None,
+            fee: None,
+        })?),
+    })
+}
+
+/// Returns StdResult<Vec<CosmosMsg>> of the transfers returning unsold consignment.  Co-sellers
+/// get back what they consigned, and the rest is returned to the consignment recipient
+///
+/// # Arguments
+///
+/// * `state` - a mutable reference to the auction State
+fn return_consignment(state: &mut State) -> StdResult<Vec<CosmosMsg>> {
+    let mut messages = Vec::new();
+    let consignment_recipient = state.consignment_recipient();
+    for item in state.lot.iter_mut() {
+        if item.consigned > 0 {
+            for consignment in state.co_consignments.drain(..) {
+                let returned = consignment.amount.min(item.consigned);
+                if returned > 0 {
+                    messages.push(item.asset.transfer_msg(
+                        &state.auction_addr,
+                        consignment.consignor,
+                        Uint128(returned),
+                    )?);
+                    item.consigned -= returned;
+                }
+            }
+            if item.consigned > 0 {
+                messages.push(item.asset.transfer_msg(
+                    &state.auction_addr,
+                    consignment_recipient.clone(),
+                    Uint128(item.consigned),
+                )?);
+            }
+            item.consigned = 0;
+        }
+    }
+    Ok(messages)
+}
+
+/// Returns StdResult<Option<PayoutCallback>> of the callback after checking that its recipient
+/// is a valid address
+///
+/// # Arguments
+///
+/// * `api` - reference to the Api used to validate addresses
+/// * `callback` - optional code hash and message used to Send tokens to a contract
+fn validate_callback<A: Api>(
+    api: &A,
+    callback: Option<PayoutCallback>,
+) -> StdResult<Option<PayoutCallback>> {
+    if let Some(recipient) = callback.as_ref().and_then(|c| c.recipient.as_ref()) {
+        api.canonical_address(recipient)?;
+    }
+    Ok(callback)
+}
+
+/// Returns bool, true if the lot is a single fungible token
+///
+/// # Arguments
+///
+/// * `lot` - a reference to the assets being sold
+fn single_fungible(lot: &[LotItem]) -> bool {
+    lot.len() == 1
+        && match &lot[0].asset {
+            Asset::Snip20(_) | Asset::Native | Asset::Snip1155(_) => true,
+            Asset::Snip721(_) => false,
+        }
+}
+
+/// Returns StdResult<Vec<CosmosMsg>> of the transfers returning every remaining bid
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `state` - mutable reference to auction state
+fn refund_bids<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    state: &mut State,
+) -> StdResult<Vec<CosmosMsg>> {
+    let mut messages = Vec::new();
+    let bidders: Vec<Vec<u8>> = state.bidders.drain().collect();
+    for bidder in bidders {
+        let bid: Option<Bid> = may_load(&deps.storage, &bidder)?;
+        if let Some(bid) = bid {
+            messages.push(
+                state.bid_tokens[bid.token].asset.transfer_msg(
+                    &state.auction_addr,
+                    deps.api
+                        .human_address(&CanonicalAddr::from(bidder.as_slice()))?,
+                    Uint128(bid.escrowed()),
+                )?,
+            );
+            remove(&mut deps.storage, &bidder);
+        }
+    }
+    Ok(messages)
+}
+
+/// Returns HandleResult
+///
+/// raise a dispute over a held sale so the arbiter decides whether it completes
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+fn try_dispute<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> HandleResult {
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    let status: ResponseStatus;
+    let message: String;
+    if let Some(held) = state.held_sale.as_mut() {
+        if env.message.sender != state.seller && env.message.sender != held.winner {
+            status = Failure;
+            message = String::from("Only the seller or the winner can dispute the sale");
+        } else if state.arbiter.is_none() {
+            status = Failure;
+            message = String::from("This auction does not have an arbiter to resolve disputes");
+        } else if held.disputed {
+            status = Failure;
+            message = String::from("The sale has already been disputed");
+        } else if env.block.time >= held.release_time {
+            status = Failure;
+            message = String::from("The dispute period has ended");
+        } else {
+            held.disputed = true;
+            save(&mut deps.storage, CONFIG_KEY, &state)?;
+            status = Success;
+            message = String::from(
+                "Dispute raised.  The arbiter will decide whether to complete or reverse the sale",
+            );
+        }
+    } else {
+        status = Failure;
+        message = String::from("There is no sale being held for the dispute period");
+    }
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Status { status, message })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// complete a held sale once the dispute period has ended without a dispute
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `plain_transfer` - true if the seller wants the proceeds sent without the proceeds callback
+fn try_release_sale<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    plain_transfer: bool,
+) -> HandleResult {
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    let message = match &state.held_sale {
+        None => Some("There is no sale being held for the dispute period"),
+        Some(held) if held.disputed => {
+            Some("The sale is disputed and must be resolved by the arbiter")
+        }
+        Some(held) if env.block.time < held.release_time => {
+            Some("The dispute period has not ended")
+        }
+        _ => None,
+    };
+    if let Some(message) = message {
+        return Ok(HandleResponse {
+            messages: vec![],
+            log: vec![],
+            data: Some(to_binary(&HandleAnswer::CloseAuction {
+                status: Failure,
+                message: String::from(message),
+                winning_bid: None,
+                amount_returned: None,
+                fee: None,
+            })?),
+        });
+    }
+    // only the seller can choose to skip their own proceeds callback
+    let plain_transfer = plain_transfer && env.message.sender == state.seller;
+    complete_held_sale(deps, env, &mut state, "Sale completed", plain_transfer)
+}
+
+/// Returns HandleResult
+///
+/// the arbiter completes or reverses a disputed sale
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `complete` - true if the sale should complete, false if it should be reversed
+/// * `plain_transfer` - true if the proceeds should be sent without the proceeds callback
+fn try_resolve_dispute<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    complete: bool,
+    plain_transfer: bool,
+) -> HandleResult {
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    let message = if state.arbiter.as_ref() != Some(&env.message.sender) {
+        Some("Only the arbiter can resolve a dispute")
+    } else if !matches!(&state.held_sale, Some(held) if held.disputed || state.off_chain_goods.is_some())
+    {
+        Some("There is no disputed sale to resolve")
+    } else {
+        None
+    };
+    if let Some(message) = message {
+        return Ok(HandleResponse {
+            messages: vec![],
+            log: vec![],
+            data: Some(to_binary(&HandleAnswer::CloseAuction {
+                status: Failure,
+                message: String::from(message),
+                winning_bid: None,
+                amount_returned: None,
+                fee: None,
+            })?),
+        });
+    }
+    if complete {
+        return complete_held_sale(
+            deps,
+            env,
+            &mut state,
+            "Dispute resolved.  Sale completed",
+            plain_transfer,
+        );
+    }
+    // reverse the swap by refunding the winner and returning the consignment
+    let mut cos_msg = Vec::new();
+    if let Some(held) = state.held_sale.take() {
+        cos_msg.push(state.bid_tokens[held.bid.token].asset.transfer_msg(
+            &state.auction_addr,
+            held.winner,
+            Uint128(held.bid.amount),
+        )?);
+    }
+    cos_msg.extend(return_consignment(&mut state)?);
+    save(&mut deps.storage, CONFIG_KEY, &state)?;
+
+    Ok(HandleResponse {
+        messages: cos_msg,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::CloseAuction {
+            status: Success,
+            message: String::from(
+                "Dispute resolved.  Sale reversed, the winning bid has been refunded and the \
+                 consigned tokens have been returned",
+            ),
+            winning_bid: None,
+            amount_returned: None,
+            fee: None,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// the winner of off-chain goods confirms delivery, releasing the winning bid to the seller
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `plain_transfer` - true if the proceeds should be sent without the proceeds callback
+fn try_confirm_delivery<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    plain_transfer: bool,
+) -> HandleResult {
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    let awaiting_delivery = state.off_chain_goods.is_some()
+        && state.held_sale.as_ref().map(|held| &held.winner) == Some(&env.message.sender);
+    if !awaiting_delivery {
+        return Ok(HandleResponse {
+            messages: vec![],
+            log: vec![],
+            data: Some(to_binary(&HandleAnswer::CloseAuction {
+                status: Failure,
+                message: String::from(
+                    "Only the winner of off-chain goods awaiting delivery can confirm delivery",
+                ),
+                winning_bid: None,
+                amount_returned: None,
+                fee: None,
+            })?),
+        });
+    }
+    complete_held_sale(
+        deps,
+        env,
+        &mut state,
+        "Delivery confirmed.  Sale completed",
+        plain_transfer,
+    )
+}
+
+/// Returns HandleResult
+///
+/// settles a held sale
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `state` - mutable reference to auction state
+/// * `message` - description of why the sale was completed
+/// * `plain_transfer` - true if the proceeds should be sent without the proceeds callback
+fn complete_held_sale<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    state: &mut State,
+    message: &str,
+    plain_transfer: bool,
+) -> HandleResult {
+    let mut cos_msg = Vec::new();
+    let mut winning_bid: Option<Uint128> = None;
+    if let Some(held) = state.held_sale.take() {
+        winning_bid = Some(Uint128(held.bid.amount));
+        cos_msg = settle(
+            &deps.api,
+            state,
+            held.winner,
+            held.bid,
+            env.block.time,
+            plain_transfer,
+        )?;
+    }
+    save(&mut deps.storage, CONFIG_KEY, &state)?;
+
+    Ok(HandleResponse {
+        messages: cos_msg,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::CloseAuction {
+            status: Success,
+            message: String::from(message),
+            winning_bid,
+            amount_returned: None,
+            fee: state.fee.as_ref().map(|_| Uint128(state.fee_paid)),
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// closes the auction and sends all the tokens in escrow to where they belong
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `only_if_bids` - true if auction should stay open if there are no bids
+/// * `return_all` - true if being called from the return_all fallback plan
+/// * `cancel` - true if the auction should be closed without a sale
+fn try_finalize<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    only_if_bids: bool,
+    return_all: bool,
+    cancel: bool,
+) -> HandleResult {
+    let mut state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    // the remaining bids are backups while the winner has time to pay the balance
+    if state.awaiting_payment.is_some() {
+        return Ok(HandleResponse {
+            messages: vec![],
+            log: vec![],
+            data: Some(to_binary(&HandleAnswer::CloseAuction {
+                status: Failure,
+                message: String::from(
+                    "The winner has until the payment deadline to pay the balance of their bid",
+                ),
+                winning_bid: None,
+                amount_returned: None,
+                fee: None,
+            })?),
+        });
+    }
+    // can only do a return_all if the auction is closed
+    if return_all && !state.is_completed {
+        return Ok(HandleResponse {
+            messages: vec![],
+            log: vec![],
+            data: Some(to_binary(&HandleAnswer::CloseAuction {
+                status: Failure,
+                message: String::from(
+                    "return_all can only be executed after the auction has ended",
+                ),
+                winning_bid: None,
+                amount_returned: None,
+                fee: None,
+            })?),
+        });
+    }
+    // if an approver set was configured, only approvers can finalize or cancel
+    let needs_approvals = !return_all && state.approvers.is_some();
+    let authorized = if let (true, Some(approvers)) = (needs_approvals, &state.approvers) {
+        approvers.addresses.contains(&env.message.sender)
+    } else {
+        let permission = if cancel {
+            OperatorPermission::Cancel
+        } else {
+            OperatorPermission::Finalize
+        };
+        state.is_authorized(&env.message.sender, permission)
+    };
+    // if not authorized, can't finalize, but you can return_all
+    if !return_all && !authorized {
+        let message = if cancel && needs_approvals {
+            "Only an approver can cancel the auction"
+        } else if cancel {
+            "Only auction creator or an authorized operator can cancel the auction"
+        } else if needs_approvals {
+            "Only an approver can finalize the sale"
+        } else {
+            "Only auction creator or an authorized operator can finalize the sale"
+        };
+        return Ok(HandleResponse {
+            messages: vec![],
+            log: vec![],
+            data: Some(to_binary(&HandleAnswer::CloseAuction {
+                status: Failure,
+                message: String::from(message),
+                winning_bid: None,
+                amount_returned: None,
+                fee: None,
+            })?),
+        });
+    }
+    // if there are no active bids, and owner only wants to close if bids
+    if !state.is_completed && only_if_bids && state.bidders.is_empty() && state.consortia.is_empty()
+    {
+        return Ok(HandleResponse {
+            messages: vec![],
+            log: vec![],
+            data: Some(to_binary(&HandleAnswer::CloseAuction {
+                status: Failure,
+                message: String::from("Did not close because there are no active bids"),
+                winning_bid: None,
+                amount_returned: None,
+                fee: None,
+            })?),
+        });
+    }
+    // record the approval and wait until enough approvers have agreed to finalize or cancel
+    let threshold = state
+        .approvers
+        .as_ref()
+        .map(|approvers| approvers.threshold);
+    if let (false, true, Some(threshold)) = (state.is_completed, needs_approvals, threshold) {
+        let approvals = if cancel {
+            &mut state.cancel_approvals
+        } else {
+            &mut state.approvals
+        };
+        let recorded = !approvals.contains(&env.message.sender);
+        if recorded {
+            approvals.push(env.message.sender.clone());
+        }
+        let collected = approvals.len();
+        if recorded {
+            save(&mut deps.storage, CONFIG_KEY, &state)?;
+        }
+        if (collected as u32) < threshold {
+            return Ok(HandleResponse {
+                messages: vec![],
+                log: vec![],
+                data: Some(to_binary(&HandleAnswer::Status {
+                    status: Success,
+                    message: format!(
+                        "{} recorded.  {} of {} required approvals have been collected",
+                        if cancel {
+                            "Cancellation approval"
+                        } else {
+                            "Approval"
+                        },
+                        collected,
+                        threshold
+                    ),
+                })?),
+            });
+        }
+    }
+    let mut cos_msg = Vec::new();
+    let mut update_state = false;
+    let mut winning_amount: Option<Uint128> = None;
+    let mut amount_returned: Option<Uint128> = None;
+    let mut consignment_returned = false;
+    let mut held_until: Option<u64> = None;
+    let mut payment_deadline: Option<u64> = None;
+
+    let no_bids = state.bidders.is_empty() && state.consortia.is_empty();
+    // if there were bids
+    if !no_bids {
+        // load all the bids
+        struct OwnedBid {
+            pub bidder: CanonicalAddr,
+            pub bid: Bid,
+            pub consortium: Option<String>,
+        }
+        let mut bid_list: Vec<OwnedBid> = Vec::new();
+        for bidder in &state.bidders {
+            let bid: Option<Bid> = may_load(&deps.storage, bidder.as_slice())?;
+            if let Some(found_bid) = bid {
+                bid_list.push(OwnedBid {
+                    bidder: CanonicalAddr::from(bidder.as_slice()),
+                    bid: found_bid,
+                    consortium: None,
+                });
+            }
+        }
+        for consortium in &state.consortia {
+            let pool: Option<Consortium> = may_load(&deps.storage, &consortium_key(consortium))?;
+            if let Some(pool) = pool {
+                bid_list.push(OwnedBid {
+                    bidder: CanonicalAddr::default(),
+                    bid: pool.bid,
+                    consortium: Some(consortium.clone()),
+                });
+            }
+        }
+        // an allowance or the seller's balance may have been lowered since it was checked.  A
+        // query that fails, such as after the seller changes their viewing key, is treated the
+        // same way so that the bids can still be returned
+        if state.tokens_consigned && !state.is_completed && !cancel && state.allowance_key.is_some()
+        {
+            let available = available_allowance(&deps.querier, &env, &state).unwrap_or(0);
+            if available < state.lot[0].amount {
+                state.lot[0].consigned = 0;
+                state.tokens_consigned = false;
+            }
+        }
+        // closing an auction that has been fully consigned
+        if state.tokens_consigned && !state.is_completed && !cancel {
+            bid_list.sort_by(|a, b| {
+                a.bid
+                    .value
+                    .cmp(&b.bid.value)
+                    .then(b.bid.timestamp.cmp(&a.bid.timestamp))
+            });
+            // a consortium still short of the minimum bid can not win
+            let top_qualifies =
+                matches!(bid_list.last(), Some(top) if top.bid.value >= state.minimum_bid);
+            let winning_bid = if top_qualifies { bid_list.pop() } else { None };
+            // if there was a winner, swap the tokens
+            if let Some(winning_bid) = winning_bid {
+                update_state = true;
+                winning_amount = Some(Uint128(winning_bid.bid.amount));
+                if let Some(consortium) = winning_bid.consortium {
+                    state.consortia.remove(&consortium);
+                    cos_msg.extend(settle_consortium(
+                        deps,
+                        &mut state,
+                        consortium,
+                        winning_bid.bid,
+                        env.block.time,
+                    )?);
+                } else {
+                    let winner = deps.api.human_address(&winning_bid.bidder)?;
+                    let window = state.deposit.as_ref().map(|terms| terms.payment_window);
+                    if let (Some(window), true) =
+                        (window, winning_bid.bid.escrowed() < winning_bid.bid.amount)
+                    {
+                        // the other bids stay in escrow in case the winner defaults
+                        let deadline = deadline_after(env.block.time, window)?;
+                        payment_deadline = Some(deadline);
+                        state.awaiting_payment = Some(PendingPayment {
+                            winner,
+                            bid: winning_bid.bid,
+                            deadline,
+                        });
+                        bid_list.clear();
+                    } else {
+                        let (messages, release_time) = close_sale(
+                            &deps.api,
+                            &mut state,
+                            winner,
+                            winning_bid.bid,
+                            env.block.time,
+                        )?;
+                        cos_msg.extend(messages);
+                        held_until = release_time;
+                    }
+                    remove(&mut deps.storage, &winning_bid.bidder.as_slice());
+                    state
+                        .bidders
+                        .remove(&winning_bid.bidder.as_slice().to_vec());
+                }
+            }
+        }
+        // loops through all remaining bids to return them to the bidders
+        for losing_bid in &bid_list {
+            // refund every contributor to a losing consortium
+            if let Some(consortium) = &losing_bid.consortium {
+                let key = consortium_key(consortium);
+                let pool: Consortium = load(&deps.storage, &key)?;
+                for contribution in pool.contributions {
+                    cos_msg.push(state.bid_tokens[pool.bid.token].asset.transfer_msg(
+                        &state.auction_addr,
+                        contribution.contributor,
+                        Uint128(contribution.amount),
+                    )?);
+                }
+                remove(&mut deps.storage, &key);
+                state.consortia.remove(consortium);
+            } else {
+                cos_msg.push(state.bid_tokens[losing_bid.bid.token].asset.transfer_msg(
+                    &state.auction_addr,
+                    deps.api.human_address(&losing_bid.bidder)?,
+                    Uint128(losing_bid.bid.escrowed()),
+                )?);
+                remove(&mut deps.storage, &losing_bid.bidder.as_slice());
+                state.bidders.remove(&losing_bid.bidder.as_slice().to_vec());
+            }
+            update_state = true;
+        }
+    }
+    // return any tokens that have been consigned to the auction owner (can happen if owner
+    // finalized the auction before consigning the full sale amount or if there were no bids)
+    let single_item = state.lot.len() == 1;
+    // allowance-backed tokens never left the seller
+    if state.allowance_key.is_some() {
+        state.lot[0].consigned = 0;
+    }
+    // consignment of a held sale stays in escrow until it is released or reversed, and
+    // consignment of a sale awaiting payment stays until it is paid or every bidder defaults
+    if state.held_sale.is_none() && state.awaiting_payment.is_none() {
+        if state.lot.iter().any(|item| item.consigned > 0) {
+            if !return_all {
+                consignment_returned = true;
+                // amounts of different tokens in a bundle can not be summed
+                if single_item {
+                    amount_returned = Some(Uint128(state.lot[0].consigned));
+                }
+            }
+            update_state = true;
+        }
+        cos_msg.extend(return_consignment(&mut state)?);
+    }
+    // mark that auction had ended
+    if !state.is_completed {
+        state.is_completed = true;
+        update_state = true;
+    }
+    if update_state {
+        save(&mut deps.storage, CONFIG_KEY, &state)?;
+    }
+
+    let fee_paid = state.fee.as_ref().map(|_| Uint128(state.fee_paid));
+    // operators are told where the tokens went instead of being told they received them
+    let recipient = if env.message.sender == state.seller {
+        "You have"
+    } else {
+        "The seller has"
+    };
+    let proceeds_recipient = if state.proceeds_address.is_some() {
+        "The proceeds address has"
+    } else {
+        recipient
+    };
+    let return_recipient = if state.consignment_return_address.is_some() {
+        "The consignment return address has"
+    } else {
+        recipient
+    };
+    let log_msg = if let Some(deadline) = payment_deadline {
+        format!(
+            "Sale finalized.  The winner must pay the balance of their bid by {}, or their \
+             deposit will be forfeited and the lot offered to the next highest bidder",
+            deadline
+        )
+    } else if let (Some(release_time), Some(_)) = (held_until, &state.off_chain_goods) {
+        format!(
+            "Sale finalized.  The winning bid will be held until the winner calls \
+             confirm_delivery or until {}, after which anyone can call release_sale",
+            release_time
+        )
+    } else if let Some(release_time) = held_until {
+        format!(
+            "Sale finalized.  The winning bid and sold tokens will be held until {} in case of \
+             a dispute, after which anyone can call release_sale",
+            release_time
+        )
+    } else if let (Some(_), Some(duration)) = (&winning_amount, state.stream_duration) {
+        format!(
+            "Sale finalized.  The proceeds will be streamed over {} seconds and can be collected \
+             with claim_proceeds",
+            duration
+        )
+    } else if winning_amount.is_some() && !state.payees.is_empty() {
+        "Sale finalized.  The winning bid tokens have been split between the payees".to_string()
+    } else if winning_amount.is_some() {
+        format!(
+            "Sale finalized.  {} been sent the winning bid tokens",
+            proceeds_recipient
+        )
+    } else if consignment_returned {
+        let cause = if cancel {
+            " because the auction was cancelled"
+        } else if !state.tokens_consigned {
+            " because the full sale amount was not consigned"
+        } else if no_bids {
+            " because there were no active bids"
+        } else {
+            ""
+        };
+        format!(
+            "Auction closed.  {} been returned the consigned tokens{}",
+            return_recipient, cause
+        )
+    } else if return_all {
+        "Outstanding funds have been returned".to_string()
+    } else if cancel {
+        "Auction has been cancelled".to_string()
+    } else {
+        "Auction has been closed".to_string()
+    };
+    Ok(HandleResponse {
+        messages: cos_msg,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::CloseAuction {
+            status: Success,
+            message: log_msg,
+            winning_bid: winning_amount,
+            amount_returned,
+            fee: winning_amount
+                .and(fee_paid)
+                .filter(|_| held_until.is_none() && payment_deadline.is_none()),
+        })?),
+    })
+}
+
+/// Returns StdResult<(Vec<CosmosMsg>, Option<u64>)> of the transfers that complete the swap,
+/// and the time the sale can be released if it is held instead
+///
+/// # Arguments
+///
+/// * `api` - reference to the Api used to convert addresses
+/// * `state` - a mutable reference to the auction State
+/// * `winner` - address of the winning bidder
+/// * `bid` - the winning Bid
+/// * `time` - current block time in seconds since epoch
+fn close_sale<A: Api>(
+    api: &A,
+    state: &mut State,
+    winner: HumanAddr,
+    bid: Bid,
+    time: u64,
+) -> StdResult<(Vec<CosmosMsg>, Option<u64>)> {
+    // with an arbiter, both sides of the swap are held for the dispute period, and the winning
+    // bid for off-chain goods is held until delivery is confirmed
+    if state.arbiter.is_some() || state.off_chain_goods.is_some() {
+        let release_time = time.checked_add(state.dispute_period).ok_or_else(|| {
+            StdError::generic_err("Dispute period or delivery timeout is too long")
+        })?;
+        state.held_sale = Some(HeldSale {
+            winner,
+            bid,
+            release_time,
+            disputed: false,
+        });
+        Ok((Vec::new(), Some(release_time)))
+    } else {
+        Ok((settle(api, state, winner, bid, time, false)?, None))
+    }
+}
+
+/// Returns StdResult<Vec<CosmosMsg>> of the transfers that complete the swap between the seller
+/// and a winning consortium, splitting the sold tokens pro-rata to the contributions
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `state` - a mutable reference to the auction State
+/// * `consortium` - id of the winning consortium
+/// * `bid` - the consortium's shared Bid
+/// * `time` - current block time in seconds since epoch
+fn settle_consortium<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    state: &mut State,
+    consortium: String,
+    bid: Bid,
+    time: u64,
+) -> StdResult<Vec<CosmosMsg>> {
+    let pool: Consortium = load(&deps.storage, &consortium_key(&consortium))?;
+    let mut messages = pay_proceeds(state, bid.token, bid.amount, None, time, false)?;
+    let shares = pool.shares(state.lot[0].amount)?;
+    for (contribution, share) in pool.contributions.into_iter().zip(shares) {
+        if share > 0 {
+            messages.push(state.lot[0].asset.transfer_msg(
+                &state.auction_addr,
+                contribution.contributor,
+                Uint128(share),
+            )?);
+        }
+    }
+    state.lot[0].consigned = 0;
+    state.winning_consortium = Some(consortium);
+    state.winning_bid = bid.amount;
+    state.winning_bid_token = bid.token;
+    Ok(messages)
+}
+
+/// Returns StdResult<Vec<CosmosMsg>> of the transfers that complete the swap between the seller
+/// and the winner
+///
+/// # Arguments
+///
+/// * `api` - reference to the Api used to convert addresses
+/// * `state` - a mutable reference to the auction State
+/// * `winner` - address of the winning bidder
+/// * `bid` - the winning Bid
+/// * `time` - current block time in seconds since epoch
+/// * `plain_transfer` - true if the proceeds should be sent without the proceeds callback
+fn settle<A: Api>(
+    api: &A,
+    state: &mut State,
+    winner: HumanAddr,
+    bid: Bid,
+    time: u64,
+    plain_transfer: bool,
+) -> StdResult<Vec<CosmosMsg>> {
+    let referrer = match &bid.referrer {
+        Some(raw) => Some(api.human_address(raw)?),
+        None => None,
+    };
+    let mut messages = pay_proceeds(state, bid.token, bid.amount, referrer, time, plain_transfer)?;
+    // vesting tokens stay in escrow until the winner claims them
+    if state.vesting.is_some() {
+        state.vesting_start = Some(time);
+        state.lot[0].consigned = 0;
+    } else {
+        for item in state.lot.iter_mut() {
+            // SNIP-20 tokens paid out with a callback wait in escrow for the winner to claim
+            // them, so a receiving contract that rejects them can not stop the sale settling
+            let claimed_later = bid.payout.is_some() && matches!(item.asset, Asset::Snip20(_));
+            // pull allowance-backed tokens straight from the seller
+            if let (Some(_), Asset::Snip20(info)) = (&state.allowance_key, &item.asset) {
+                let recipient = if claimed_later {
+                    state.auction_addr.clone()
+                } else {
+                    winner.clone()
+                };
+                messages.push(transfer_from_msg(
+                    state.seller.clone(),
+                    recipient,
+                    Uint128(item.amount),
+                    None,
+                    BLOCK_SIZE,
+                    info.code_hash.clone(),
+                    info.address.clone(),
+                )?);
+            } else if !claimed_later {
+                messages.push(item.asset.transfer_msg(
+                    &state.auction_addr,
+                    winner.clone(),
+                    Uint128(item.amount),
+                )?);
+            }
+            if claimed_later {
+                state.payout_unclaimed = true;
+            }
+            item.consigned = 0;
+        }
+    }
+    state.winner = Some(winner);
+    state.winner_payout = bid.payout;
+    state.winning_bid = bid.amount;
+    state.winning_bid_token = bid.token;
+    Ok(messages)
+}
+
+/// Returns StdResult<Vec<CosmosMsg>> of the transfers paying out the winning bid after taking
+/// out any platform fee and referral reward.  If the proceeds are streamed, only the fee,
+/// referral reward and co-sellers' shares are paid out now
+///
+/// # Arguments
+///
+/// * `state` - a mutable reference to the auction State
+/// * `token` - index of the bid token the winning bid was made in
+/// * `amount` - amount of the winning bid
+/// * `referrer` - optional address that referred the winning bid
+/// * `time` - current block time in seconds since epoch
+/// * `plain_transfer` - true if the proceeds should be sent without the proceeds callback
+fn pay_proceeds(
+    state: &mut State,
+    token: usize,
+    amount: u128,
+    referrer: Option<HumanAddr>,
+    time: u64,
+    plain_transfer: bool,
+) -> StdResult<Vec<CosmosMsg>> {
+    let (mut messages, fee_amount, proceeds) = split_proceeds(state, token, amount, referrer)?;
+    state.fee_paid = fee_amount;
+    if state.stream_duration.is_some() {
+        state.stream_start = Some(time);
+        state.stream_total = proceeds;
+    } else {
+        messages.extend(distribute_proceeds(state, token, proceeds, plain_transfer)?);
+    }
+    Ok(messages)
+}
+
+/// Returns StdResult<(Vec<CosmosMsg>, u128, u128)> of the transfers paying any platform fee,
+/// referral reward and co-sellers' shares out of an amount of a bid token, the fee taken, and
+/// the seller's remaining proceeds
+///
+/// # Arguments
+///
+/// * `state` - a mutable reference to the auction State
+/// * `token` - index of the bid token the amount is in
+/// * `amount` - amount to split
+/// * `referrer` - optional address that referred the winning bid
+fn split_proceeds(
+    state: &mut State,
+    token: usize,
+    amount: u128,
+    referrer: Option<HumanAddr>,
+) -> StdResult<(Vec<CosmosMsg>, u128, u128)> {
+    let mut messages = Vec::new();
+    let mut fee_amount = 0;
+    let asset = &state.bid_tokens[token].asset;
+    let mut proceeds = amount;
+    if let Some(fee) = &state.fee {
+        let rated = amount
+            .checked_mul(fee.rate_bps as u128)
+            .ok_or_else(|| StdError::generic_err("Fee is too large"))?
+            / 10000;
+        let minimum = match fee.minimum {
+            Some(minimum) => state.bid_tokens[token].amount_worth(minimum.u128())?,
+            None => 0,
+        };
+        fee_amount = rated.max(minimum).min(amount);
+        if fee_amount > 0 {
+            messages.push(asset.transfer_msg(
+                &state.auction_addr,
+                fee.collector.clone(),
+                Uint128(fee_amount),
+            )?);
+        }
+        proceeds -= fee_amount;
+    }
+    if let (Some(rate), Some(referrer)) = (state.referral_rate_bps, referrer) {
+        let reward = proceeds
+            .checked_mul(rate as u128)
+            .ok_or_else(|| StdError::generic_err("Referral reward is too large"))?
+            / 10000;
+        if reward > 0 {
+            messages.push(asset.transfer_msg(
+                &state.auction_addr,
+                referrer.clone(),
+                Uint128(reward),
+            )?);
+        }
+        proceeds -= reward;
+        state.referral_paid = reward;
+        state.referrer = Some(referrer);
+    }
+    // co-sellers are paid pro-rata to what they consigned, and the seller keeps the rest
+    let mut seller_proceeds = proceeds;
+    for consignment in &state.co_consignments {
+        let share = mul_div(proceeds, consignment.amount, state.lot[0].amount)
+            .ok_or_else(|| StdError::generic_err("Co-seller share is too large"))?;
+        if share > 0 {
+            messages.push(state.bid_tokens[token].asset.transfer_msg(
+                &state.auction_addr,
+                consignment.consignor.clone(),
+                Uint128(share),
+            )?);
+        }
+        seller_proceeds -= share;
+    }
+    Ok((messages, fee_amount, seller_proceeds))
+}
+
+/// Returns StdResult<u64> of the deadline for a winner to pay the balance of their bid
+///
+/// # Arguments
+///
+/// * `time` - current block time in seconds since epoch
+/// * `window` - seconds the winner has to pay
+fn deadline_after(time: u64, window: u64) -> StdResult<u64> {
+    time.checked_add(window)
+        .ok_or_else(|| StdError::generic_err("Payment window is too long"))
+}
+
+/// Returns StdResult<Vec<CosmosMsg>> of the transfers sending proceeds to the proceeds
+/// recipient or splitting them between any payees
+///
+/// # Arguments
+///
+/// * `state` - a reference to the auction State
+/// * `token` - index of the bid token the proceeds are in
+/// * `proceeds` - amount of proceeds to send
+/// * `plain_transfer` - true if the proceeds should be sent without the proceeds callback
+fn distribute_proceeds(
+    state: &State,
+    token: usize,
+    proceeds: u128,
+    plain_transfer: bool,
+) -> StdResult<Vec<CosmosMsg>> {
+    let mut messages = Vec::new();
+    let asset = &state.bid_tokens[token].asset;
+    if state.payees.is_empty() {
+        if proceeds > 0 {
+            messages.push(asset.payout_msg(
+                &state.auction_addr,
+                state.proceeds_recipient(),
+                Uint128(proceeds),
+                state.proceeds_callback.as_ref().filter(|_| !plain_transfer),
+            )?);
+        }
+        return Ok(messages);
+    }
+    // each payee's share is rounded down, and the rounding dust goes to the first payee
+    let shares = state
+        .payees
+        .iter()
+        .map(|payee| {
+            proceeds
+                .checked_mul(payee.share_bps as u128)
+                .map(|product| product / 10000)
+                .ok_or_else(|| StdError::generic_err("Proceeds are too large to split"))
+        })
+        .collect::<StdResult<Vec<u128>>>()?;
+    let dust = proceeds - shares.iter().sum::<u128>();
+    for (i, (payee, share)) in state.payees.iter().zip(shares).enumerate() {
+        let amount = if i == 0 { share + dust } else { share };
+        if amount > 0 {
+            messages.push(asset.transfer_msg(
+                &state.auction_addr,
+                payee.address.clone(),
+                Uint128(amount),
+            )?);
+        }
+    }
+    Ok(messages)
+}
+
+/////////////////////////////////////// Query /////////////////////////////////////
+/// Returns QueryResult
+///
+/// # Arguments
+///
+/// * `deps` - reference to Extern containing all the contract's external dependencies
+/// * `msg` - QueryMsg passed in with the query call
+pub fn query<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, msg: QueryMsg) -> QueryResult {
+    let response = match msg {
+        QueryMsg::AuctionInfo { .. } => try_query_info(deps),
+        QueryMsg::VestingInfo { time, .. } => try_query_vesting(deps, time),
+        QueryMsg::ProceedsInfo { time, .. } => try_query_proceeds(deps, time),
+    };
+    pad_query_result(response, BLOCK_SIZE)
+}
+
+/// Returns QueryResult displaying the vested and claimed amounts
+///
+/// # Arguments
+///
+/// * `deps` - reference to Extern containing all the contract's external dependencies
+/// * `time` - current time in seconds since epoch
+fn try_query_vesting<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    time: u64,
+) -> QueryResult {
+    let state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    let schedule = state
+        .vesting
+        .clone()
+        .ok_or_else(|| StdError::generic_err("This auction does not have a vesting schedule"))?;
+    to_binary(&QueryAnswer::VestingInfo {
+        schedule,
+        start: state.vesting_start,
+        total: Uint128(state.lot[0].amount),
+        vested: Uint128(state.vested(time)?),
+        claimed: Uint128(state.vesting_claimed),
+    })
+}
+
+/// Returns QueryResult displaying the accrued and claimed streamed proceeds
+///
+/// # Arguments
+///
+/// * `deps` - reference to Extern containing all the contract's external dependencies
+/// * `time` - current time in seconds since epoch
+fn try_query_proceeds<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    time: u64,
+) -> QueryResult {
+    let state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    let duration = state
+        .stream_duration
+        .ok_or_else(|| StdError::generic_err("This auction does not stream its proceeds"))?;
+    to_binary(&QueryAnswer::ProceedsInfo {
+        duration,
+        start: state.stream_start,
+        total: Uint128(state.stream_total),
+        accrued: Uint128(state.streamed(time)?),
+        claimed: Uint128(state.stream_claimed),
+    })
+}
+
+/// Returns QueryResult
+///
+/// # Arguments
+///
+/// * `deps` - reference to Extern containing all the contract's external dependencies
+fn try_query_info<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> QueryResult {
+    let state: State = load(&deps.storage, CONFIG_KEY)?;
+
+    // get sell token info
+    let mut sell_token: Option<Token> = None;
+    let mut sell_amount: Option<Uint128> = None;
+    let mut bundle: Option<Vec<BundleToken>> = None;
+    if state.lot.len() == 1 {
+        sell_token = Some(state.lot[0].asset.token(&deps.querier)?);
+        sell_amount = Some(Uint128(state.lot[0].amount));
+    } else if !state.lot.is_empty() {
+        let mut tokens = Vec::new();
+        for item in &state.lot {
+            tokens.push(BundleToken {
+                token: item.asset.token(&deps.querier)?,
+                amount: Uint128(item.amount),
+                consigned: Uint128(item.consigned),
+            });
+        }
+        bundle = Some(tokens);
+    }
+    // get bid token info
+    let mut bid_token: Option<Token> = None;
+    let mut bid_tokens: Option<Vec<BidTokenRate>> = None;
+    if state.bid_tokens.len() == 1 {
+        bid_token = Some(state.bid_tokens[0].asset.token(&deps.querier)?);
+    } else {
+        let mut tokens = Vec::new();
+        for accepted in &state.bid_tokens {
+            tokens.push(BidTokenRate {
+                token: accepted.asset.token(&deps.querier)?,
+                rate: ConversionRate {
+                    numerator: Uint128(accepted.rate_numerator),
+                    denominator: Uint128(accepted.rate_denominator),
+                },
+            });
+        }
+        bid_tokens = Some(tokens);
+    }
+
+    // build status string
+    let status = if state.awaiting_payment.is_some() {
+        String::from("Closed: Awaiting payment of the winning bid's balance")
+    } else if let Some(held) = &state.held_sale {
+        if held.disputed {
+            String::from("Closed: Sale is disputed and awaiting the arbiter's decision")
+        } else if state.off_chain_goods.is_some() {
+            String::from("Closed: Awaiting delivery confirmation")
+        } else {
+            String::from("Closed: Sale is being held for the dispute period")
+        }
+    } else if state.is_completed {
+        let locked = if !state.bidders.is_empty()
+            || !state.consortia.is_empty()
+            || state.lot.iter().any(|i| i.consigned > 0)
+        {
+            ", but found outstanding balances.  Please run either retract_bid to \
+                retrieve your non-winning bid, or return_all to return all outstanding bids/\
+                consignment."
+        } else {
+            ""
+        };
+        format!("Closed{}", locked)
+    } else {
+        let consign = if !state.tokens_consigned { " NOT" } else { "" };
+        let accepting = if state.is_paused {
+            "Paused"
+        } else {
+            "Accepting bids"
+        };
+        let how = if state.allowance_key.is_some() {
+            "approved for transfer by"
+        } else {
+            "consigned to"
+        };
+        if state.off_chain_goods.is_some() {
+            format!("{}: Off-chain goods", accepting)
+        } else {
+            format!(
+                "{}: Token(s) to be sold have{} been {} the auction",
+                accepting, consign, how
+            )
+        }
+    };
+
+    let winning_bid = if state.winning_bid == 0 {
+        None
+    } else {
+        Some(Uint128(state.winning_bid))
+    };
+    let winning_bid_token = match (
+        &winning_bid,
+        &state.bid_tokens[state.winning_bid_token].asset,
+    ) {
+        (Some(_), Asset::Snip20(info)) if state.bid_tokens.len() > 1 => Some(info.address.clone()),
+        _ => None,
+    };
+
+    let (approvals, cancel_approvals) = if state.approvers.is_some() {
+        (Some(state.approvals), Some(state.cancel_approvals))
+    } else {
+        (None, None)
+    };
+    let change_approvals = if state.pending_change.is_some() {
+        Some(state.change_approvals)
+    } else {
+        None
+    };
+
+    let fee_paid = match (&winning_bid, &state.fee) {
+        (Some(_), Some(_)) => Some(Uint128(state.fee_paid)),
+        _ => None,
+    };
+    to_binary(&QueryAnswer::AuctionInfo(Box::new(AuctionInfo {
+        sell_token,
+        bid_token,
+        bid_tokens,
+        sell_amount,
+        bundle,
+        minimum_bid: Uint128(state.minimum_bid),
+        description: state.description,
+        auction_address: state.auction_addr,
+        status,
+        winning_bid,
+        winning_bid_token,
+        approvers: state.approvers,
+        approvals,
+        cancel_approvals,
+        pending_change: state.pending_change,
+        change_approvals,
+        fee: state.fee,
+        fee_paid,
+        payees: if state.payees.is_empty() {
+            None
+        } else {
+            Some(state.payees)
+        },
+        proceeds_address: state.proceeds_address,
+        consignment_return_address: state.consignment_return_address,
+        proceeds_callback: state.proceeds_callback,
+        arbiter: state.arbiter,
+        dispute_period: if state.dispute_period == 0 && state.held_sale.is_none() {
+            None
+        } else {
+            Some(state.dispute_period)
+        },
+        release_time: state.held_sale.map(|held| held.release_time),
+        off_chain_goods: state.off_chain_goods,
+        deposit: state.deposit,
+        payment_deadline: state.awaiting_payment.map(|pending| pending.deadline),
+        retraction: state.retraction,
+        bid_fee: state.bid_fee,
+        referral_rate_bps: state.referral_rate_bps,
+        consortium_bids: if state.consortium_bids {
+            Some(true)
+        } else {
+            None
+        },
+        co_sellers: if state.co_sellers.is_empty() {
+            None
+        } else {
+            Some(state.co_sellers)
+        },
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{
+        coins, from_slice, BankMsg, Empty, QuerierResult, QueryRequest, WasmMsg, WasmQuery,
+    };
+
+    fn init_auction(extra: &str) -> Extern<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies(20, &[]);
+        let msg: InitMsg = serde_json::from_str(&format!(
+            "{{\"sell_contract\":{{\"code_hash\":\"sellhash\",\"address\":\"selltoken\"}},\
+             \"sell_amount\":\"1000\",\"minimum_bid\":\"100\"{}}}",
+            extra
+        ))
+        .unwrap();
+        init(&mut deps, mock_env("seller", &[]), msg).unwrap();
+        let consign = HandleMsg::Receive {
+            sender: HumanAddr::from("seller"),
+            from: HumanAddr::from("seller"),
+            amount: Uint128(1000),
+            msg: None,
+        };
+        handle(&mut deps, mock_env("selltoken", &[]), consign).unwrap();
+        deps
+    }
+
+    fn place_bid(
+        deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+        bidder: &str,
+        sent: u128,
+        bid_amount: Option<u128>,
+    ) {
+        let msg = HandleMsg::PlaceBid {
+            payout: None,
+            bid_amount: bid_amount.map(Uint128),
+            referrer: None,
+            consortium: None,
+        };
+        handle(deps, mock_env(bidder, &coins(sent, "uscrt")), msg).unwrap();
+    }
+
+    fn at_time(
+        deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+        sender: &str,
+        time: u64,
+        msg: HandleMsg,
+    ) -> HandleResponse {
+        let mut env = mock_env(sender, &[]);
+        env.block.time = time;
+        handle(deps, env, msg).unwrap()
+    }
+
+    fn bank_sends(messages: &[CosmosMsg]) -> Vec<(HumanAddr, u128)> {
+        messages
+            .iter()
+            .filter_map(|msg| match msg {
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address, amount, ..
+                }) => Some((to_address.clone(), amount[0].amount.u128())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn contract_actions(messages: &[CosmosMsg]) -> Vec<(HumanAddr, String)> {
+        messages
+            .iter()
+            .filter_map(|msg| match msg {
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr, msg, ..
+                }) => {
+                    // the action is the first key of the serialized message
+                    let json = String::from_utf8(msg.as_slice().to_vec()).unwrap();
+                    let action = json.split('"').nth(1).unwrap().to_string();
+                    Some((contract_addr.clone(), action))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bid_changes_clear_approvals_to_finalize() {
+        let mut deps = init_auction(
+            ",\"approvers\":{\"addresses\":[\"approver1\",\"approver2\"],\"threshold\":2}",
+        );
+        place_bid(&mut deps, "alice", 100, None);
+        let finalize = || HandleMsg::Finalize {
+            only_if_bids: false,
+        };
+        handle(&mut deps, mock_env("approver1", &[]), finalize()).unwrap();
+        handle(
+            &mut deps,
+            mock_env("approver1", &[]),
+            HandleMsg::CancelAuction {},
+        )
+        .unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert_eq!(state.approvals, vec![HumanAddr::from("approver1")]);
+
+        place_bid(&mut deps, "bob", 200, None);
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(state.approvals.is_empty());
+        assert_eq!(state.cancel_approvals, vec![HumanAddr::from("approver1")]);
+
+        // the seller can not cancel past the approvers
+        handle(
+            &mut deps,
+            mock_env("seller", &[]),
+            HandleMsg::CancelAuction {},
+        )
+        .unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(!state.is_completed);
+
+        handle(&mut deps, mock_env("approver2", &[]), finalize()).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(!state.is_completed);
+        handle(&mut deps, mock_env("approver1", &[]), finalize()).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(state.is_completed);
+        assert_eq!(state.winning_bid, 200);
+    }
+
+    #[test]
+    fn seller_can_release_held_sale_without_proceeds_callback() {
+        let release = |sender: &str| {
+            let mut deps = init_auction(
+                ",\"bid_contract\":{\"code_hash\":\"bidhash\",\"address\":\"bidtoken\"},\
+                 \"arbiter\":\"arbiter\",\"dispute_period\":100,\
+                 \"proceeds_address\":\"vault\",\"proceeds_callback\":{\"code_hash\":\"vaulthash\"}",
+            );
+            let bid = HandleMsg::Receive {
+                sender: HumanAddr::from("alice"),
+                from: HumanAddr::from("alice"),
+                amount: Uint128(500),
+                msg: None,
+            };
+            handle(&mut deps, mock_env("bidtoken", &[]), bid).unwrap();
+            let start = mock_env("seller", &[]).block.time;
+            at_time(
+                &mut deps,
+                "seller",
+                start,
+                HandleMsg::Finalize {
+                    only_if_bids: false,
+                },
+            );
+            let resp = at_time(
+                &mut deps,
+                sender,
+                start + 100,
+                HandleMsg::ReleaseSale {
+                    plain_transfer: true,
+                },
+            );
+            contract_actions(&resp.messages)
+                .into_iter()
+                .filter(|(contract, _)| contract == &HumanAddr::from("bidtoken"))
+                .map(|(_, action)| action)
+                .collect::<Vec<String>>()
+        };
+        // only the seller can skip the proceeds callback
+        assert_eq!(release("anyone"), vec![String::from("send")]);
+        assert_eq!(release("seller"), vec![String::from("transfer")]);
+    }
+
+    #[test]
+    fn unreadable_bid_msg_returns_the_tokens() {
+        let mut deps =
+            init_auction(",\"bid_contract\":{\"code_hash\":\"bidhash\",\"address\":\"bidtoken\"}");
+        let bid = HandleMsg::Receive {
+            sender: HumanAddr::from("alice"),
+            from: HumanAddr::from("alice"),
+            amount: Uint128(500),
+            msg: Some(Binary::from(b"not bid options".to_vec())),
+        };
+        let resp = handle(&mut deps, mock_env("bidtoken", &[]), bid).unwrap();
+        assert_eq!(
+            contract_actions(&resp.messages),
+            vec![(HumanAddr::from("bidtoken"), String::from("transfer"))]
+        );
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(state.bidders.is_empty());
+    }
+
+    #[test]
+    fn seller_transfer_waits_for_approvers() {
+        let mut deps = init_auction(
+            ",\"approvers\":{\"addresses\":[\"approver1\",\"approver2\"],\"threshold\":2}",
+        );
+        place_bid(&mut deps, "alice", 100, None);
+        let approve = |address: &str| HandleMsg::ApproveChange {
+            change: PendingChange::SellerTransfer {
+                address: HumanAddr::from(address),
+            },
+        };
+        let propose = HandleMsg::ProposeSellerTransfer {
+            address: HumanAddr::from("newseller"),
+        };
+        handle(&mut deps, mock_env("seller", &[]), propose).unwrap();
+        let accept = || HandleMsg::AcceptSellerTransfer {};
+        handle(&mut deps, mock_env("newseller", &[]), accept()).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert_eq!(state.seller, HumanAddr::from("seller"));
+
+        // the seller can not approve, and approvals must be for the pending change
+        handle(&mut deps, mock_env("seller", &[]), approve("newseller")).unwrap();
+        handle(&mut deps, mock_env("approver1", &[]), approve("other")).unwrap();
+        handle(&mut deps, mock_env("approver1", &[]), approve("newseller")).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert_eq!(state.change_approvals, vec![HumanAddr::from("approver1")]);
+        assert!(state.pending_seller.is_none());
+        handle(&mut deps, mock_env("approver2", &[]), approve("newseller")).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert_eq!(state.pending_seller, Some(HumanAddr::from("newseller")));
+        assert!(state.pending_change.is_none());
+
+        // approvals to finalize were given for the previous owner's sale
+        let finalize = HandleMsg::Finalize {
+            only_if_bids: false,
+        };
+        handle(&mut deps, mock_env("approver1", &[]), finalize).unwrap();
+        handle(&mut deps, mock_env("newseller", &[]), accept()).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert_eq!(state.seller, HumanAddr::from("newseller"));
+        assert!(state.approvals.is_empty());
+    }
+
+    #[test]
+    fn payout_address_change_waits_for_approvers() {
+        let mut deps = init_auction(
+            ",\"approvers\":{\"addresses\":[\"approver1\",\"approver2\"],\"threshold\":1}",
+        );
+        place_bid(&mut deps, "alice", 100, None);
+        let finalize = HandleMsg::Finalize {
+            only_if_bids: false,
+        };
+        let change = PendingChange::PayoutAddresses {
+            proceeds_address: Some(HumanAddr::from("vault")),
+            consignment_return_address: None,
+            proceeds_callback: None,
+        };
+        let set = HandleMsg::SetPayoutAddresses {
+            proceeds_address: Some(HumanAddr::from("vault")),
+            consignment_return_address: None,
+            proceeds_callback: None,
+        };
+        handle(&mut deps, mock_env("seller", &[]), set).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(state.proceeds_address.is_none());
+        assert_eq!(state.pending_change, Some(change.clone()));
+
+        // approving the change clears the approvals to finalize
+        let mut state = state;
+        state.approvals.push(HumanAddr::from("approver2"));
+        save(&mut deps.storage, CONFIG_KEY, &state).unwrap();
+        let approve = HandleMsg::ApproveChange { change };
+        handle(&mut deps, mock_env("approver1", &[]), approve).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert_eq!(state.proceeds_address, Some(HumanAddr::from("vault")));
+        assert!(state.approvals.is_empty());
+
+        let resp = handle(&mut deps, mock_env("approver1", &[]), finalize).unwrap();
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("vault"), 100)]
+        );
+    }
+
+    #[test]
+    fn consignment_withdrawal_waits_for_approvers() {
+        let mut deps = init_auction(
+            ",\"approvers\":{\"addresses\":[\"approver1\",\"approver2\"],\"threshold\":1}",
+        );
+        let withdraw = HandleMsg::WithdrawConsignment {
+            amount: Some(Uint128(400)),
+        };
+        let resp = handle(&mut deps, mock_env("seller", &[]), withdraw).unwrap();
+        assert!(resp.messages.is_empty());
+        let change = PendingChange::WithdrawConsignment {
+            consignor: HumanAddr::from("seller"),
+            amount: Some(Uint128(400)),
+        };
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert_eq!(state.pending_change, Some(change.clone()));
+        assert!(state.tokens_consigned);
+
+        let approve = HandleMsg::ApproveChange { change };
+        let resp = handle(&mut deps, mock_env("approver2", &[]), approve).unwrap();
+        assert_eq!(
+            contract_actions(&resp.messages),
+            vec![(HumanAddr::from("selltoken"), String::from("transfer"))]
+        );
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(state.pending_change.is_none());
+        assert_eq!(state.lot[0].consigned, 600);
+        assert!(!state.tokens_consigned);
+    }
+
+    #[test]
+    fn accepting_seller_transfer_clears_payees() {
+        let mut deps = init_auction(
+            ",\"payees\":[{\"address\":\"payee1\",\"share_bps\":6000},\
+             {\"address\":\"payee2\",\"share_bps\":4000}]",
+        );
+        let propose = HandleMsg::ProposeSellerTransfer {
+            address: HumanAddr::from("newseller"),
+        };
+        handle(&mut deps, mock_env("seller", &[]), propose).unwrap();
+        let accept = HandleMsg::AcceptSellerTransfer {};
+        handle(&mut deps, mock_env("newseller", &[]), accept).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(state.payees.is_empty());
+
+        place_bid(&mut deps, "alice", 100, None);
+        let finalize = HandleMsg::Finalize {
+            only_if_bids: false,
+        };
+        let resp = handle(&mut deps, mock_env("newseller", &[]), finalize).unwrap();
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("newseller"), 100)]
+        );
+    }
+
+    #[test]
+    fn invalid_payout_address_is_rejected() {
+        let mut deps = init_auction("");
+        let set = HandleMsg::SetPayoutAddresses {
+            proceeds_address: None,
+            consignment_return_address: Some(HumanAddr::from("x")),
+            proceeds_callback: None,
+        };
+        assert!(handle(&mut deps, mock_env("seller", &[]), set).is_err());
+    }
+
+    #[test]
+    fn retraction_is_locked_until_the_auction_closes() {
+        let start = mock_env("seller", &[]).block.time;
+        let mut deps = init_auction(&format!(
+            ",\"retraction\":{{\"ends_at\":{},\"lock_period\":100}}",
+            start + 1000
+        ));
+        place_bid(&mut deps, "alice", 100, None);
+        let retract = || HandleMsg::RetractBid {};
+        let resp = at_time(&mut deps, "alice", start + 900, retract());
+        assert!(resp.messages.is_empty());
+        // bids stay locked after the scheduled end while the auction is open
+        let resp = at_time(&mut deps, "alice", start + 2000, retract());
+        assert!(resp.messages.is_empty());
+        let resp = at_time(&mut deps, "alice", start + 899, retract());
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("alice"), 100)]
+        );
+    }
+
+    #[test]
+    fn retraction_fee_is_kept_while_the_auction_is_open() {
+        let mut deps = init_auction(
+            ",\"fee\":{\"rate_bps\":0,\"collector\":\"collector\"},\
+             \"retraction\":{\"fee_bps\":1000,\"to_fee_collector\":true}",
+        );
+        place_bid(&mut deps, "alice", 1000, None);
+        let resp = handle(&mut deps, mock_env("alice", &[]), HandleMsg::RetractBid {}).unwrap();
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![
+                (HumanAddr::from("collector"), 100),
+                (HumanAddr::from("alice"), 900),
+            ]
+        );
+    }
+
+    #[test]
+    fn minimum_fee_is_converted_to_the_winning_token() {
+        let deps = init_auction(
+            ",\"fee\":{\"rate_bps\":0,\"minimum\":\"100\",\"collector\":\"collector\"}",
+        );
+        let mut state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        // 3 tokens are worth 2 in the unit of account, so the minimum is 150 tokens
+        state.bid_tokens[0].rate_numerator = 2;
+        state.bid_tokens[0].rate_denominator = 3;
+
+        let messages = pay_proceeds(&mut state, 0, 10000, None, 1000, false).unwrap();
+        assert_eq!(
+            bank_sends(&messages),
+            vec![
+                (HumanAddr::from("collector"), 150),
+                (HumanAddr::from("seller"), 9850),
+            ]
+        );
+        assert_eq!(state.fee_paid, 150);
+    }
+
+    #[test]
+    fn pay_proceeds_takes_the_platform_fee() {
+        let deps = init_auction(",\"fee\":{\"rate_bps\":250,\"collector\":\"collector\"}");
+        let mut state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+
+        let messages = pay_proceeds(&mut state, 0, 10000, None, 1000, false).unwrap();
+        assert_eq!(
+            bank_sends(&messages),
+            vec![
+                (HumanAddr::from("collector"), 250),
+                (HumanAddr::from("seller"), 9750),
+            ]
+        );
+        assert_eq!(state.fee_paid, 250);
+    }
+
+    #[test]
+    fn pay_proceeds_splits_between_payees() {
+        let deps = init_auction(
+            ",\"payees\":[{\"address\":\"payee1\",\"share_bps\":6000},\
+             {\"address\":\"payee2\",\"share_bps\":4000}]",
+        );
+        let mut state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+
+        // the rounding dust goes to the first payee
+        let messages = pay_proceeds(&mut state, 0, 1001, None, 1000, false).unwrap();
+        assert_eq!(
+            bank_sends(&messages),
+            vec![
+                (HumanAddr::from("payee1"), 601),
+                (HumanAddr::from("payee2"), 400),
+            ]
+        );
+    }
+
+    #[test]
+    fn pay_proceeds_pays_referral_after_the_fee() {
+        let deps = init_auction(
+            ",\"fee\":{\"rate_bps\":1000,\"collector\":\"collector\"},\
+             \"referral_rate_bps\":500,\"referrers\":[\"referrer\"]",
+        );
+        let mut state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+
+        let referrer = Some(HumanAddr::from("referrer"));
+        let messages = pay_proceeds(&mut state, 0, 10000, referrer, 1000, false).unwrap();
+        // 5% of the 9000 left after the 10% fee
+        assert_eq!(
+            bank_sends(&messages),
+            vec![
+                (HumanAddr::from("collector"), 1000),
+                (HumanAddr::from("referrer"), 450),
+                (HumanAddr::from("seller"), 8550),
+            ]
+        );
+        assert_eq!(state.referral_paid, 450);
+        assert_eq!(state.referrer, Some(HumanAddr::from("referrer")));
+    }
+
+    #[test]
+    fn pay_proceeds_without_referrer_pays_no_reward() {
+        let deps = init_auction(",\"referral_rate_bps\":500,\"referrers\":[\"referrer\"]");
+        let mut state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+
+        let messages = pay_proceeds(&mut state, 0, 10000, None, 1000, false).unwrap();
+        assert_eq!(
+            bank_sends(&messages),
+            vec![(HumanAddr::from("seller"), 10000)]
+        );
+        assert_eq!(state.referral_paid, 0);
+    }
+
+    #[test]
+    fn pay_proceeds_pays_co_sellers_after_fee_and_referral() {
+        let deps = init_auction(
+            ",\"fee\":{\"rate_bps\":1000,\"collector\":\"collector\"},\
+             \"referral_rate_bps\":500,\"referrers\":[\"referrer\"],\
+             \"co_sellers\":[\"coseller\"]",
+        );
+        let mut state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        state.co_consignments.push(Consignment {
+            consignor: HumanAddr::from("coseller"),
+            amount: 250,
+        });
+
+        let messages = pay_proceeds(
+            &mut state,
+            0,
+            10000,
+            Some(HumanAddr::from("referrer")),
+            1000,
+            false,
+        )
+        .unwrap();
+        // 10% fee, then 5% of the remaining 9000 to the referrer, then a quarter of the
+        // remaining 8550 to the co-seller, rounded down
+        assert_eq!(
+            bank_sends(&messages),
+            vec![
+                (HumanAddr::from("collector"), 1000),
+                (HumanAddr::from("referrer"), 450),
+                (HumanAddr::from("coseller"), 2137),
+                (HumanAddr::from("seller"), 6413),
+            ]
+        );
+        assert_eq!(state.fee_paid, 1000);
+        assert_eq!(state.referral_paid, 450);
+        assert_eq!(state.referrer, Some(HumanAddr::from("referrer")));
+    }
+
+    #[test]
+    fn declare_default_promotes_next_bidder() {
+        let mut deps = init_auction(",\"deposit\":{\"rate_bps\":1000,\"payment_window\":100}");
+        place_bid(&mut deps, "alice", 100, Some(1000));
+        place_bid(&mut deps, "bob", 90, Some(900));
+        let start = mock_env("seller", &[]).block.time;
+        at_time(
+            &mut deps,
+            "seller",
+            start,
+            HandleMsg::Finalize {
+                only_if_bids: false,
+            },
+        );
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        let pending = state.awaiting_payment.unwrap();
+        assert_eq!(pending.winner, HumanAddr::from("alice"));
+        assert_eq!(pending.deadline, start + 100);
+
+        // nothing happens before the deadline
+        let resp = at_time(
+            &mut deps,
+            "anyone",
+            start + 99,
+            HandleMsg::DeclareDefault {},
+        );
+        assert!(resp.messages.is_empty());
+
+        let resp = at_time(
+            &mut deps,
+            "anyone",
+            start + 100,
+            HandleMsg::DeclareDefault {},
+        );
+        // alice's deposit is forfeited to the seller, and bob gets a new payment window
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("seller"), 100)]
+        );
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        let pending = state.awaiting_payment.unwrap();
+        assert_eq!(pending.winner, HumanAddr::from("bob"));
+        assert_eq!(pending.bid.amount, 900);
+        assert_eq!(pending.deadline, start + 200);
+        assert!(state.bidders.is_empty());
+    }
+
+    #[test]
+    fn declare_default_returns_consignment_when_bids_are_exhausted() {
+        let mut deps = init_auction(",\"deposit\":{\"rate_bps\":1000,\"payment_window\":100}");
+        place_bid(&mut deps, "alice", 100, Some(1000));
+        let start = mock_env("seller", &[]).block.time;
+        at_time(
+            &mut deps,
+            "seller",
+            start,
+            HandleMsg::Finalize {
+                only_if_bids: false,
+            },
+        );
+
+        let resp = at_time(
+            &mut deps,
+            "anyone",
+            start + 100,
+            HandleMsg::DeclareDefault {},
+        );
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("seller"), 100)]
+        );
+        // the only other message returns the consigned sell tokens
+        assert_eq!(resp.messages.len(), 2);
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(state.awaiting_payment.is_none());
+        assert_eq!(state.lot[0].consigned, 0);
+        assert!(state.winner.is_none());
+    }
+
+    #[test]
+    fn seller_transfer_hands_over_the_auction() {
+        let mut deps = init_auction(",\"proceeds_address\":\"vault\"");
+        let set_operator = HandleMsg::SetOperator {
+            address: HumanAddr::from("operator"),
+            permissions: vec![OperatorPermission::Finalize],
+        };
+        handle(&mut deps, mock_env("seller", &[]), set_operator).unwrap();
+        let propose = || HandleMsg::ProposeSellerTransfer {
+            address: HumanAddr::from("newseller"),
+        };
+        let accept = || HandleMsg::AcceptSellerTransfer {};
+
+        // only the seller can propose, and only the proposed address can accept
+        handle(&mut deps, mock_env("newseller", &[]), propose()).unwrap();
+        handle(&mut deps, mock_env("newseller", &[]), accept()).unwrap();
+        handle(&mut deps, mock_env("seller", &[]), propose()).unwrap();
+        handle(&mut deps, mock_env("someone", &[]), accept()).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert_eq!(state.seller, HumanAddr::from("seller"));
+        assert_eq!(state.pending_seller, Some(HumanAddr::from("newseller")));
+
+        handle(&mut deps, mock_env("newseller", &[]), accept()).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert_eq!(state.seller, HumanAddr::from("newseller"));
+        assert!(state.pending_seller.is_none());
+        assert!(state.operators.is_empty());
+        assert!(state.proceeds_address.is_none());
+
+        // the previous owner can no longer finalize, and the new owner is paid
+        place_bid(&mut deps, "alice", 100, None);
+        let finalize = || HandleMsg::Finalize {
+            only_if_bids: false,
+        };
+        let resp = handle(&mut deps, mock_env("seller", &[]), finalize()).unwrap();
+        assert!(resp.messages.is_empty());
+        let resp = handle(&mut deps, mock_env("newseller", &[]), finalize()).unwrap();
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("newseller"), 100)]
+        );
+    }
+
+    #[test]
+    fn operators_act_only_within_their_permissions() {
+        let mut deps = init_auction("");
+        let set_operator = |permissions: Vec<OperatorPermission>| HandleMsg::SetOperator {
+            address: HumanAddr::from("operator"),
+            permissions,
+        };
+        // only the seller can set operators
+        handle(
+            &mut deps,
+            mock_env("operator", &[]),
+            set_operator(vec![OperatorPermission::Cancel]),
+        )
+        .unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(state.operators.is_empty());
+
+        handle(
+            &mut deps,
+            mock_env("seller", &[]),
+            set_operator(vec![
+                OperatorPermission::Finalize,
+                OperatorPermission::UpdateDescription,
+            ]),
+        )
+        .unwrap();
+        let describe = HandleMsg::ChangeDescription {
+            description: Some(String::from("by the operator")),
+        };
+        handle(&mut deps, mock_env("operator", &[]), describe).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert_eq!(state.description, Some(String::from("by the operator")));
+
+        let cancel = HandleMsg::CancelAuction {};
+        handle(&mut deps, mock_env("operator", &[]), cancel).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(!state.is_completed);
+
+        // the operator closes the sale, but the seller is paid
+        place_bid(&mut deps, "alice", 100, None);
+        let finalize = HandleMsg::Finalize {
+            only_if_bids: false,
+        };
+        let resp = handle(&mut deps, mock_env("operator", &[]), finalize).unwrap();
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("seller"), 100)]
+        );
+    }
+
+    #[test]
+    fn removed_operator_loses_its_permissions() {
+        let mut deps = init_auction("");
+        let set_operator = |permissions: Vec<OperatorPermission>| HandleMsg::SetOperator {
+            address: HumanAddr::from("operator"),
+            permissions,
+        };
+        handle(
+            &mut deps,
+            mock_env("seller", &[]),
+            set_operator(vec![OperatorPermission::Cancel]),
+        )
+        .unwrap();
+        handle(&mut deps, mock_env("seller", &[]), set_operator(vec![])).unwrap();
+        let cancel = HandleMsg::CancelAuction {};
+        handle(&mut deps, mock_env("operator", &[]), cancel).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(state.operators.is_empty());
+        assert!(!state.is_completed);
+    }
+
+    #[test]
+    fn paused_auction_returns_new_bids() {
+        let mut deps = init_auction(",\"admin\":\"pauser\"");
+        place_bid(&mut deps, "alice", 100, None);
+        let pause = |paused: bool| HandleMsg::SetPaused { paused };
+        handle(&mut deps, mock_env("someone", &[]), pause(true)).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(!state.is_paused);
+
+        handle(&mut deps, mock_env("pauser", &[]), pause(true)).unwrap();
+        let bid = HandleMsg::PlaceBid {
+            payout: None,
+            bid_amount: None,
+            referrer: None,
+            consortium: None,
+        };
+        let resp = handle(&mut deps, mock_env("bob", &coins(200, "uscrt")), bid).unwrap();
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("bob"), 200)]
+        );
+        // retracting still works while paused
+        let retract = HandleMsg::RetractBid {};
+        let resp = handle(&mut deps, mock_env("alice", &[]), retract).unwrap();
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("alice"), 100)]
+        );
+
+        handle(&mut deps, mock_env("seller", &[]), pause(false)).unwrap();
+        place_bid(&mut deps, "bob", 200, None);
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(!state.is_paused);
+        assert_eq!(state.bidders.len(), 1);
+    }
+
+    #[test]
+    fn native_sale_settles_with_bank_sends() {
+        let mut deps = mock_dependencies(20, &[]);
+        let msg: InitMsg = serde_json::from_str(
+            "{\"bid_contract\":{\"code_hash\":\"bidhash\",\"address\":\"bidtoken\"},\
+             \"sell_amount\":\"1000\",\"minimum_bid\":\"100\"}",
+        )
+        .unwrap();
+        init(&mut deps, mock_env("seller", &[]), msg).unwrap();
+        // SCRT can only be consigned by the seller
+        let consign = || HandleMsg::Consign {};
+        let resp = handle(
+            &mut deps,
+            mock_env("someone", &coins(1000, "uscrt")),
+            consign(),
+        )
+        .unwrap();
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("someone"), 1000)]
+        );
+        handle(
+            &mut deps,
+            mock_env("seller", &coins(1000, "uscrt")),
+            consign(),
+        )
+        .unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(state.tokens_consigned);
+
+        let bid = HandleMsg::Receive {
+            sender: HumanAddr::from("alice"),
+            from: HumanAddr::from("alice"),
+            amount: Uint128(500),
+            msg: None,
+        };
+        handle(&mut deps, mock_env("bidtoken", &[]), bid).unwrap();
+        let finalize = HandleMsg::Finalize {
+            only_if_bids: false,
+        };
+        let resp = handle(&mut deps, mock_env("seller", &[]), finalize).unwrap();
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("alice"), 1000)]
+        );
+        assert_eq!(
+            contract_actions(&resp.messages),
+            vec![(HumanAddr::from("bidtoken"), String::from("transfer"))]
+        );
+    }
+
+    #[test]
+    fn native_bids_must_be_scrt() {
+        let mut deps = init_auction("");
+        let bid = || HandleMsg::PlaceBid {
+            payout: None,
+            bid_amount: None,
+            referrer: None,
+            consortium: None,
+        };
+        assert!(handle(&mut deps, mock_env("alice", &coins(100, "uatom")), bid()).is_err());
+
+        // a larger bid from the same address returns the earlier one
+        place_bid(&mut deps, "alice", 100, None);
+        let resp = handle(&mut deps, mock_env("alice", &coins(150, "uscrt")), bid()).unwrap();
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("alice"), 100)]
+        );
+    }
+
+    #[test]
+    fn nft_sale_consigns_and_settles() {
+        let mut deps = mock_dependencies(20, &[]);
+        let msg: InitMsg = serde_json::from_str(
+            "{\"sell_nft\":{\"contract\":{\"code_hash\":\"nfthash\",\"address\":\"nftcontract\"},\
+             \"token_id\":\"42\"},\"minimum_bid\":\"100\"}",
+        )
+        .unwrap();
+        init(&mut deps, mock_env("seller", &[]), msg).unwrap();
+        let receive = |token_id: &str| HandleMsg::ReceiveNft {
+            sender: HumanAddr::from("seller"),
+            token_id: String::from(token_id),
+            msg: None,
+        };
+        // a different NFT from the same contract is sent back
+        let resp = handle(&mut deps, mock_env("nftcontract", &[]), receive("7")).unwrap();
+        assert_eq!(
+            contract_actions(&resp.messages),
+            vec![(HumanAddr::from("nftcontract"), String::from("transfer_nft"))]
+        );
+        handle(&mut deps, mock_env("nftcontract", &[]), receive("42")).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(state.tokens_consigned);
+
+        place_bid(&mut deps, "alice", 100, None);
+        let finalize = HandleMsg::Finalize {
+            only_if_bids: false,
+        };
+        let resp = handle(&mut deps, mock_env("seller", &[]), finalize).unwrap();
+        assert_eq!(
+            contract_actions(&resp.messages),
+            vec![(HumanAddr::from("nftcontract"), String::from("transfer_nft"))]
+        );
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("seller"), 100)]
+        );
+    }
+
+    #[test]
+    fn bundle_sale_needs_every_item_consigned() {
+        let mut deps = mock_dependencies(20, &[]);
+        let msg: InitMsg = serde_json::from_str(
+            "{\"bundle\":[{\"snip20\":{\"contract\":{\"code_hash\":\"sellhash\",\
+             \"address\":\"selltoken\"},\"amount\":\"500\"}},{\"snip721\":{\"contract\":\
+             {\"code_hash\":\"nfthash\",\"address\":\"nftcontract\"},\"token_id\":\"42\"}}],\
+             \"minimum_bid\":\"100\"}",
+        )
+        .unwrap();
+        init(&mut deps, mock_env("seller", &[]), msg).unwrap();
+        let consign = HandleMsg::Receive {
+            sender: HumanAddr::from("seller"),
+            from: HumanAddr::from("seller"),
+            amount: Uint128(500),
+            msg: None,
+        };
+        handle(&mut deps, mock_env("selltoken", &[]), consign).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(!state.tokens_consigned);
+        let consign = HandleMsg::ReceiveNft {
+            sender: HumanAddr::from("seller"),
+            token_id: String::from("42"),
+            msg: None,
+        };
+        handle(&mut deps, mock_env("nftcontract", &[]), consign).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(state.tokens_consigned);
+
+        place_bid(&mut deps, "alice", 100, None);
+        let finalize = HandleMsg::Finalize {
+            only_if_bids: false,
+        };
+        let resp = handle(&mut deps, mock_env("seller", &[]), finalize).unwrap();
+        assert_eq!(
+            contract_actions(&resp.messages),
+            vec![
+                (HumanAddr::from("selltoken"), String::from("transfer")),
+                (HumanAddr::from("nftcontract"), String::from("transfer_nft")),
+            ]
+        );
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("seller"), 100)]
+        );
+    }
+
+    #[test]
+    fn snip1155_sale_consigns_and_settles() {
+        let mut deps = mock_dependencies(20, &[]);
+        let msg: InitMsg = serde_json::from_str(
+            "{\"sell_snip1155\":{\"contract\":{\"code_hash\":\"multihash\",\
+             \"address\":\"multitoken\"},\"token_id\":\"gold\"},\"sell_amount\":\"30\",\
+             \"minimum_bid\":\"100\"}",
+        )
+        .unwrap();
+        init(&mut deps, mock_env("seller", &[]), msg).unwrap();
+        let receive = |token_id: &str, amount: u128| HandleMsg::Snip1155Receive {
+            sender: HumanAddr::from("seller"),
+            token_id: String::from(token_id),
+            from: HumanAddr::from("seller"),
+            amount: Uint128(amount),
+            memo: None,
+            msg: None,
+        };
+        // a different token ID from the same contract is sent back
+        let resp = handle(
+            &mut deps,
+            mock_env("multitoken", &[]),
+            receive("silver", 30),
+        )
+        .unwrap();
+        assert_eq!(
+            contract_actions(&resp.messages),
+            vec![(HumanAddr::from("multitoken"), String::from("transfer"))]
+        );
+        handle(&mut deps, mock_env("multitoken", &[]), receive("gold", 20)).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(!state.tokens_consigned);
+        handle(&mut deps, mock_env("multitoken", &[]), receive("gold", 10)).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(state.tokens_consigned);
+
+        place_bid(&mut deps, "alice", 100, None);
+        let finalize = HandleMsg::Finalize {
+            only_if_bids: false,
+        };
+        let resp = handle(&mut deps, mock_env("seller", &[]), finalize).unwrap();
+        assert_eq!(
+            contract_actions(&resp.messages),
+            vec![(HumanAddr::from("multitoken"), String::from("transfer"))]
+        );
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("seller"), 100)]
+        );
+    }
+
+    #[test]
+    fn bids_in_several_tokens_are_ranked_by_value() {
+        let mut deps = init_auction(
+            ",\"bid_tokens\":[\
+             {\"contract\":{\"code_hash\":\"hash\",\"address\":\"tokena\"},\
+             \"rate\":{\"numerator\":\"1\",\"denominator\":\"1\"}},\
+             {\"contract\":{\"code_hash\":\"hash\",\"address\":\"tokenb\"},\
+             \"rate\":{\"numerator\":\"2\",\"denominator\":\"1\"}}]",
+        );
+        let bid = |deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+                   token: &str,
+                   bidder: &str,
+                   amount: u128,
+                   time: u64| {
+            let msg = HandleMsg::Receive {
+                sender: HumanAddr::from(bidder),
+                from: HumanAddr::from(bidder),
+                amount: Uint128(amount),
+                msg: None,
+            };
+            at_time(deps, token, time, msg);
+        };
+        // worth 300, 400 and 400 in the unit of account.  The earlier of the tied bids wins
+        bid(&mut deps, "tokena", "alice", 300, 1_000);
+        bid(&mut deps, "tokenb", "bob", 200, 2_000);
+        bid(&mut deps, "tokena", "carol", 400, 3_000);
+        let finalize = HandleMsg::Finalize {
+            only_if_bids: false,
+        };
+        let resp = handle(&mut deps, mock_env("seller", &[]), finalize).unwrap();
+        let mut actions = contract_actions(&resp.messages);
+        actions.sort();
+        assert_eq!(
+            actions,
+            vec![
+                (HumanAddr::from("selltoken"), String::from("transfer")),
+                (HumanAddr::from("tokena"), String::from("transfer")),
+                (HumanAddr::from("tokena"), String::from("transfer")),
+                (HumanAddr::from("tokenb"), String::from("transfer")),
+            ]
+        );
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert_eq!(state.winner, Some(HumanAddr::from("bob")));
+        assert_eq!(state.winning_bid, 200);
+        assert_eq!(state.winning_bid_token, 1);
+    }
+
+    /// answers the sell token's balance and allowance queries for an auction consigned by
+    /// allowance
+    struct AllowanceQuerier {
+        balance: u128,
+        allowance: u128,
+    }
+
+    impl Querier for AllowanceQuerier {
+        fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+            let query = match from_slice::<QueryRequest<Empty>>(bin_request) {
+                Ok(QueryRequest::Wasm(WasmQuery::Smart { msg, .. })) => {
+                    String::from_utf8(msg.as_slice().to_vec()).unwrap()
+                }
+                _ => return Ok(Err(StdError::generic_err("Unexpected query"))),
+            };
+            let response = if query.starts_with("{\"balance\"") {
+                format!("{{\"balance\":{{\"amount\":\"{}\"}}}}", self.balance)
+            } else {
+                format!(
+                    "{{\"allowance\":{{\"spender\":\"auction\",\"owner\":\"seller\",\
+                     \"allowance\":\"{}\",\"expiration\":null}}}}",
+                    self.allowance
+                )
+            };
+            Ok(Ok(Binary::from(response.as_bytes())))
+        }
+    }
+
+    fn init_allowance_auction() -> Extern<MockStorage, MockApi, AllowanceQuerier> {
+        let mut deps = Extern {
+            storage: MockStorage::new(),
+            api: MockApi::new(20),
+            querier: AllowanceQuerier {
+                balance: 1000,
+                allowance: 1000,
+            },
+        };
+        let msg: InitMsg = serde_json::from_str(
+            "{\"sell_contract\":{\"code_hash\":\"sellhash\",\"address\":\"selltoken\"},\
+             \"sell_amount\":\"1000\",\"minimum_bid\":\"100\",\
+             \"consign_by_allowance\":true,\"entropy\":\"entropy\"}",
+        )
+        .unwrap();
+        init(&mut deps, mock_env("seller", &[]), msg).unwrap();
+        let consign = HandleMsg::ConsignAllowance {
+            balance_key: String::from("key"),
+        };
+        handle(&mut deps, mock_env("seller", &[]), consign).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(state.tokens_consigned);
+        handle(
+            &mut deps,
+            mock_env("alice", &coins(200, "uscrt")),
+            HandleMsg::PlaceBid {
+                payout: None,
+                bid_amount: None,
+                referrer: None,
+                consortium: None,
+            },
+        )
+        .unwrap();
+        deps
+    }
+
+    #[test]
+    fn allowance_is_pulled_from_the_seller_at_finalize() {
+        let finalize = || HandleMsg::Finalize {
+            only_if_bids: false,
+        };
+        let mut deps = init_allowance_auction();
+        let resp = handle(&mut deps, mock_env("seller", &[]), finalize()).unwrap();
+        assert_eq!(
+            contract_actions(&resp.messages),
+            vec![(HumanAddr::from("selltoken"), String::from("transfer_from"))]
+        );
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("seller"), 200)]
+        );
+
+        // a lowered allowance is caught at finalize and the bid is returned
+        let mut deps = init_allowance_auction();
+        deps.querier.allowance = 500;
+        let resp = handle(&mut deps, mock_env("seller", &[]), finalize()).unwrap();
+        assert!(contract_actions(&resp.messages).is_empty());
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("alice"), 200)]
+        );
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(!state.tokens_consigned);
+        assert_eq!(state.winner, None);
+    }
+
+    #[test]
+    fn vested_tokens_are_claimed_as_they_unlock() {
+        let mut deps = init_auction(",\"vesting\":{\"cliff\":100,\"duration\":1000}");
+        place_bid(&mut deps, "alice", 200, None);
+        let finalize = HandleMsg::Finalize {
+            only_if_bids: false,
+        };
+        let resp = at_time(&mut deps, "seller", 10_000, finalize);
+        // the sold tokens stay in escrow while they vest
+        assert!(contract_actions(&resp.messages).is_empty());
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("seller"), 200)]
+        );
+        let claim = || HandleMsg::Claim {
+            plain_transfer: false,
+        };
+        let resp = at_time(&mut deps, "bob", 10_500, claim());
+        assert!(resp.messages.is_empty());
+        let resp = at_time(&mut deps, "alice", 10_050, claim());
+        assert!(resp.messages.is_empty());
+
+        let resp = at_time(&mut deps, "alice", 10_500, claim());
+        assert_eq!(
+            contract_actions(&resp.messages),
+            vec![(HumanAddr::from("selltoken"), String::from("transfer"))]
+        );
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert_eq!(state.vesting_claimed, 500);
+        let resp = at_time(&mut deps, "alice", 10_500, claim());
+        assert!(resp.messages.is_empty());
+
+        at_time(&mut deps, "alice", 12_000, claim());
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert_eq!(state.vesting_claimed, 1000);
+    }
+
+    #[test]
+    fn streamed_proceeds_are_claimed_as_they_accrue() {
+        let mut deps = init_auction(",\"proceeds_stream_duration\":1000");
+        place_bid(&mut deps, "alice", 200, None);
+        let finalize = HandleMsg::Finalize {
+            only_if_bids: false,
+        };
+        let resp = at_time(&mut deps, "seller", 10_000, finalize);
+        // the winning bid stays in escrow while it streams
+        assert!(bank_sends(&resp.messages).is_empty());
+        let claim = || HandleMsg::ClaimProceeds {
+            plain_transfer: false,
+        };
+        let resp = at_time(&mut deps, "alice", 10_500, claim());
+        assert!(resp.messages.is_empty());
+
+        let resp = at_time(&mut deps, "seller", 10_250, claim());
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("seller"), 50)]
+        );
+        let resp = at_time(&mut deps, "seller", 10_250, claim());
+        assert!(resp.messages.is_empty());
+        let resp = at_time(&mut deps, "seller", 11_500, claim());
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("seller"), 150)]
+        );
+    }
+
+    #[test]
+    fn arbiter_resolves_disputed_sales() {
+        let extra = ",\"arbiter\":\"arbiter\",\"dispute_period\":100";
+        let finalize = || HandleMsg::Finalize {
+            only_if_bids: false,
+        };
+        let release = || HandleMsg::ReleaseSale {
+            plain_transfer: false,
+        };
+        let resolve = |complete| HandleMsg::ResolveDispute {
+            complete,
+            plain_transfer: false,
+        };
+        let mut deps = init_auction(extra);
+        place_bid(&mut deps, "alice", 200, None);
+        // the sale is held for the dispute period
+        let resp = at_time(&mut deps, "seller", 10_000, finalize());
+        assert!(resp.messages.is_empty());
+        at_time(&mut deps, "bob", 10_050, HandleMsg::Dispute {});
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(!state.held_sale.unwrap().disputed);
+        at_time(&mut deps, "alice", 10_050, HandleMsg::Dispute {});
+        let resp = at_time(&mut deps, "seller", 10_200, release());
+        assert!(resp.messages.is_empty());
+        let resp = at_time(&mut deps, "seller", 10_200, resolve(false));
+        assert!(resp.messages.is_empty());
+        // reversing the sale refunds the winner and returns the consignment
+        let resp = at_time(&mut deps, "arbiter", 10_200, resolve(false));
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("alice"), 200)]
+        );
+        assert_eq!(
+            contract_actions(&resp.messages),
+            vec![(HumanAddr::from("selltoken"), String::from("transfer"))]
+        );
+
+        // an undisputed sale is released once the dispute period ends
+        let mut deps = init_auction(extra);
+        place_bid(&mut deps, "alice", 200, None);
+        at_time(&mut deps, "seller", 10_000, finalize());
+        let resp = at_time(&mut deps, "bob", 10_050, release());
+        assert!(resp.messages.is_empty());
+        at_time(&mut deps, "alice", 10_100, HandleMsg::Dispute {});
+        let resp = at_time(&mut deps, "bob", 10_100, release());
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("seller"), 200)]
+        );
+        assert_eq!(
+            contract_actions(&resp.messages),
+            vec![(HumanAddr::from("selltoken"), String::from("transfer"))]
+        );
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(state.held_sale.is_none());
+    }
+
+    #[test]
+    fn off_chain_sale_waits_for_delivery_or_timeout() {
+        let sell_goods = || {
+            let mut deps = mock_dependencies(20, &[]);
+            let msg: InitMsg = serde_json::from_str(
+                "{\"off_chain_goods\":{\"metadata\":\"painting\",\"delivery_timeout\":500},\
+                 \"minimum_bid\":\"100\"}",
+            )
+            .unwrap();
+            init(&mut deps, mock_env("seller", &[]), msg).unwrap();
+            place_bid(&mut deps, "alice", 200, None);
+            let finalize = HandleMsg::Finalize {
+                only_if_bids: false,
+            };
+            let resp = at_time(&mut deps, "seller", 10_000, finalize);
+            assert!(resp.messages.is_empty());
+            deps
+        };
+        let confirm = || HandleMsg::ConfirmDelivery {
+            plain_transfer: false,
+        };
+        let release = || HandleMsg::ReleaseSale {
+            plain_transfer: false,
+        };
+        let mut deps = sell_goods();
+        let resp = at_time(&mut deps, "seller", 10_100, confirm());
+        assert!(resp.messages.is_empty());
+        let resp = at_time(&mut deps, "alice", 10_100, confirm());
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("seller"), 200)]
+        );
+
+        // without a confirmation the bid is released after the delivery timeout
+        let mut deps = sell_goods();
+        let resp = at_time(&mut deps, "seller", 10_400, release());
+        assert!(resp.messages.is_empty());
+        let resp = at_time(&mut deps, "seller", 10_500, release());
+        assert_eq!(
+            bank_sends(&resp.messages),
+            vec![(HumanAddr::from("seller"), 200)]
+        );
+        let resp = at_time(&mut deps, "alice", 10_500, confirm());
+        assert!(resp.messages.is_empty());
+    }
+
+    #[test]
+    fn withdrawn_consignment_must_be_topped_up() {
+        let mut deps = init_auction("");
+        let withdraw = |amount| HandleMsg::WithdrawConsignment {
+            amount: Some(Uint128(amount)),
+        };
+        let resp = handle(&mut deps, mock_env("alice", &[]), withdraw(400)).unwrap();
+        assert!(resp.messages.is_empty());
+        let resp = handle(&mut deps, mock_env("seller", &[]), withdraw(1500)).unwrap();
+        assert!(resp.messages.is_empty());
+
+        let resp = handle(&mut deps, mock_env("seller", &[]), withdraw(400)).unwrap();
+        assert_eq!(
+            contract_actions(&resp.messages),
+            vec![(HumanAddr::from("selltoken"), String::from("transfer"))]
+        );
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert_eq!(state.lot[0].consigned, 600);
+        assert!(!state.tokens_consigned);
+
+        let consign = HandleMsg::Receive {
+            sender: HumanAddr::from("seller"),
+            from: HumanAddr::from("seller"),
+            amount: Uint128(400),
+            msg: None,
+        };
+        handle(&mut deps, mock_env("selltoken", &[]), consign).unwrap();
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(state.tokens_consigned);
+
+        // nothing can be withdrawn once there are bids
+        place_bid(&mut deps, "alice", 200, None);
+        let resp = handle(&mut deps, mock_env("seller", &[]), withdraw(400)).unwrap();
+        assert!(resp.messages.is_empty());
+        let state: State = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert_eq!(state.lot[0].consigned, 1000);
+    }
+}
@@ -1,20 +1,43 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Binary, CosmosMsg, HumanAddr, Querier, StdResult, Uint128};
+use cosmwasm_std::{BankMsg, Binary, Coin, CosmosMsg, HumanAddr, Querier, StdResult, Uint128};
 
-use secret_toolkit::snip20::{register_receive_msg, token_info_query, transfer_msg, TokenInfo};
+use secret_toolkit::{
+    snip20::{register_receive_msg, token_info_query, transfer_msg, TokenInfo},
+    utils::{HandleCallback, Query},
+};
 
-use crate::contract::BLOCK_SIZE;
+use crate::contract::{BLOCK_SIZE, NATIVE_DENOM};
 
 /// Instantiation message
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct InitMsg {
-    /// sell contract code hash and address
-    pub sell_contract: ContractInfo,
-    /// bid contract code hash and address
-    pub bid_contract: ContractInfo,
-    /// amount of tokens being sold
+    /// sell contract code hash and address.  Omit to sell native SCRT (uscrt) or an NFT
+    #[serde(default)]
+    pub sell_contract: Option<ContractInfo>,
+    /// SNIP-721 contract and token ID of the NFT being sold.  Omit if selling fungible tokens
+    #[serde(default)]
+    pub sell_nft: Option<NftInfo>,
+    /// SNIP-1155 contract and token ID of the tokens being sold.  Omit if not selling SNIP-1155
+    /// tokens
+    #[serde(default)]
+    pub sell_snip1155: Option<Snip1155Info>,
+    /// Optional list of assets to sell together as one lot.  If provided, sell_contract, sell_nft,
+    /// sell_snip1155, and sell_amount must be omitted
+    #[serde(default)]
+    pub bundle: Option<Vec<BundleItem>>,
+    /// bid contract code hash and address.  Omit to accept bids in native SCRT (uscrt) or to
+    /// accept several bid tokens
+    #[serde(default)]
+    pub bid_contract: Option<ContractInfo>,
+    /// Optional list of SNIP-20 tokens accepted for bids, each with a fixed conversion rate to a
+    /// common unit of account.  If provided, bid_contract must be omitted, and minimum_bid is in
+    /// the unit of account
+    #[serde(default)]
+    pub bid_tokens: Option<Vec<AcceptedToken>>,
+    /// amount of tokens being sold.  May be omitted if selling an NFT
+    #[serde(default)]
     pub sell_amount: Uint128,
     /// minimum bid that will be accepted
     pub minimum_bid: Uint128,
@@ -23,6 +46,287 @@ pub struct InitMsg {
     /// auctions for the same token, etc...
     #[serde(default)]
     pub description: Option<String>,
+    /// Optional set of addresses that must approve the finalization.  If provided, only these
+    /// addresses can finalize the auction, and the sale will not settle until the threshold number
+    /// of them have called finalize
+    #[serde(default)]
+    pub approvers: Option<ApproverSet>,
+    /// Optional address that can pause and resume the auction in addition to the seller
+    #[serde(default)]
+    pub admin: Option<HumanAddr>,
+    /// true if the seller will grant the auction an allowance for the SNIP-20 tokens being sold
+    /// instead of sending them to escrow.  The tokens are only pulled from the seller if there is
+    /// a winning bid.  Only available when selling a single SNIP-20 token
+    #[serde(default)]
+    pub consign_by_allowance: bool,
+    /// Optional random string used to create the auction's viewing key with the sell token.
+    /// Required when consigning by allowance
+    #[serde(default)]
+    pub entropy: Option<String>,
+    /// Optional platform fee taken out of the winning bid before it is paid to the seller
+    #[serde(default)]
+    pub fee: Option<FeeConfig>,
+    /// Optional list of addresses the proceeds are split between.  Shares must add up to 10000
+    /// basis points.  If omitted, the seller receives all the proceeds
+    #[serde(default)]
+    pub payees: Option<Vec<Payee>>,
+    /// Optional address that receives the proceeds instead of the seller
+    #[serde(default)]
+    pub proceeds_address: Option<HumanAddr>,
+    /// Optional address that unsold consignment is returned to instead of the seller
+    #[serde(default)]
+    pub consignment_return_address: Option<HumanAddr>,
+    /// Optional code hash and message used to Send the proceeds to a contract instead of
+    /// transferring them.  Only used if the proceeds are a SNIP-20 token
+    #[serde(default)]
+    pub proceeds_callback: Option<PayoutCallback>,
+    /// Optional schedule for releasing the sold tokens to the winner instead of sending them all
+    /// when the auction is finalized.  Only available when selling a single fungible token
+    #[serde(default)]
+    pub vesting: Option<VestingSchedule>,
+    /// Optional number of seconds over which the proceeds are streamed to the seller instead of
+    /// being paid when the auction is finalized
+    #[serde(default)]
+    pub proceeds_stream_duration: Option<u64>,
+    /// Optional address that resolves disputes.  If provided, a sale is held for the dispute
+    /// period before it completes
+    #[serde(default)]
+    pub arbiter: Option<HumanAddr>,
+    /// Optional number of seconds a sale is held so the seller or winner can dispute it.  Must be
+    /// provided with an arbiter
+    #[serde(default)]
+    pub dispute_period: Option<u64>,
+    /// Optional description of off-chain goods or services being sold.  If provided, no sell
+    /// token can be provided, and the winning bid is held until the winner confirms delivery
+    #[serde(default)]
+    pub off_chain_goods: Option<OffChainGoods>,
+    /// Optional terms letting bidders escrow only a deposit of their bid.  The winner must then
+    /// pay the balance within the payment window
+    #[serde(default)]
+    pub deposit: Option<DepositTerms>,
+    /// Optional fee kept from retracted bids, and period before the scheduled end during which
+    /// bids can not be retracted
+    #[serde(default)]
+    pub retraction: Option<RetractionTerms>,
+    /// Optional flat fee kept from the first deposit of every new bidder
+    #[serde(default)]
+    pub bid_fee: Option<BidFee>,
+    /// Optional rate in basis points of the proceeds, after any platform fee, paid to the
+    /// referrer of the winning bid
+    #[serde(default)]
+    pub referral_rate_bps: Option<u16>,
+    /// Optional addresses allowed to earn referral rewards.  Required if there is a referral rate
+    #[serde(default)]
+    pub referrers: Option<Vec<HumanAddr>>,
+    /// true if several addresses can pool their bid tokens into a shared consortium bid.  Only
+    /// available when selling a single fungible token consigned to escrow
+    #[serde(default)]
+    pub consortium_bids: bool,
+    /// Optional addresses that may consign sell tokens alongside the seller and share the
+    /// proceeds pro-rata to what they consigned.  Co-sellers share forfeited deposits, but not
+    /// retraction or placement fees.  Only available when selling a single fungible token
+    /// consigned to escrow
+    #[serde(default)]
+    pub co_sellers: Option<Vec<HumanAddr>>,
+}
+
+/// an asset in a bundle being sold
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleItem {
+    /// an amount of a SNIP-20 token
+    Snip20 {
+        /// code hash and address of the SNIP-20 contract
+        contract: ContractInfo,
+        /// amount of tokens being sold
+        amount: Uint128,
+    },
+    /// a SNIP-721 NFT
+    Snip721(NftInfo),
+    /// an amount of a SNIP-1155 token
+    Snip1155 {
+        /// SNIP-1155 contract and token ID
+        token: Snip1155Info,
+        /// amount of tokens being sold
+        amount: Uint128,
+    },
+}
+
+/// a SNIP-20 token accepted for bids
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct AcceptedToken {
+    /// code hash and address of the SNIP-20 contract
+    pub contract: ContractInfo,
+    /// fixed conversion rate from this token to the auction's unit of account
+    pub rate: ConversionRate,
+}
+
+/// fixed conversion rate to the auction's unit of account.  The value of an amount of tokens is
+/// amount * numerator / denominator
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+pub struct ConversionRate {
+    /// value of `denominator` tokens in the unit of account
+    pub numerator: Uint128,
+    /// number of tokens worth `numerator` in the unit of account
+    pub denominator: Uint128,
+}
+
+/// M-of-N set of addresses that must approve finalizing the auction
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct ApproverSet {
+    /// addresses allowed to approve
+    pub addresses: Vec<HumanAddr>,
+    /// number of approvals needed to finalize
+    pub threshold: u32,
+}
+
+/// change to the auction that is waiting for the approvers' consent
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PendingChange {
+    /// transfer of auction ownership to a new seller
+    SellerTransfer {
+        /// address being proposed as the new seller
+        address: HumanAddr,
+    },
+    /// new payout addresses set by the seller
+    PayoutAddresses {
+        /// Optional address that receives the proceeds instead of the seller
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        proceeds_address: Option<HumanAddr>,
+        /// Optional address that unsold consignment is returned to instead of the seller
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        consignment_return_address: Option<HumanAddr>,
+        /// Optional code hash and message used to Send the proceeds to a contract
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        proceeds_callback: Option<PayoutCallback>,
+    },
+    /// withdrawal of consigned tokens by the seller or a co-seller
+    WithdrawConsignment {
+        /// address withdrawing the tokens
+        consignor: HumanAddr,
+        /// Optional amount to withdraw.  Everything the consignor consigned if omitted
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        amount: Option<Uint128>,
+    },
+}
+
+/// platform fee taken out of the winning bid
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct FeeConfig {
+    /// fee rate in basis points of the winning bid
+    pub rate_bps: u16,
+    /// Optional flat minimum fee, in the unit of account if there are several bid tokens.  It is
+    /// charged in the winning bid token, converted at that token's rate and rounded up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<Uint128>,
+    /// address the fee is paid to
+    pub collector: HumanAddr,
+}
+
+/// an address receiving a share of the proceeds
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct Payee {
+    /// address of the payee
+    pub address: HumanAddr,
+    /// payee's share of the proceeds in basis points
+    pub share_bps: u16,
+}
+
+/// code hash and message used to Send SNIP-20 tokens to a contract so it receives a Receive
+/// callback
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct PayoutCallback {
+    /// Optional address of the receiving contract.  Defaults to the winner for won tokens, and
+    /// to the proceeds address (or the seller) for proceeds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipient: Option<HumanAddr>,
+    /// code hash of the receiving contract
+    pub code_hash: String,
+    /// Optional message passed to the receiving contract's Receive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub msg: Option<Binary>,
+}
+
+/// options a bidder can include as the msg of the Send call that places a bid
+#[derive(Serialize, Deserialize, JsonSchema, Default)]
+pub struct BidOptions {
+    /// Optional code hash and message used to Send the won tokens to a contract
+    #[serde(default)]
+    pub payout: Option<PayoutCallback>,
+    /// Optional full amount of the bid when only a deposit is being sent.  Only used by auctions
+    /// that accept deposit-backed bids
+    #[serde(default)]
+    pub bid_amount: Option<Uint128>,
+    /// Optional address that referred the bidder.  Ignored unless the auction pays referral
+    /// rewards to that address
+    #[serde(default)]
+    pub referrer: Option<HumanAddr>,
+    /// Optional id of the consortium bid the tokens are contributed to.  Only used by auctions
+    /// that accept consortium bids.  Ids are not owned, so anyone who knows an id can contribute
+    /// to that consortium and share in its winnings
+    #[serde(default)]
+    pub consortium: Option<String>,
+}
+
+/// cliff/linear schedule for releasing the sold tokens to the winner, starting when the auction
+/// is finalized
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct VestingSchedule {
+    /// number of seconds after the sale before any tokens can be claimed
+    pub cliff: u64,
+    /// number of seconds after the sale until all tokens are unlocked.  Tokens unlock linearly
+    /// over this period, but none can be claimed before the cliff
+    pub duration: u64,
+}
+
+/// off-chain goods or services being sold
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct OffChainGoods {
+    /// description of the goods or services
+    pub metadata: String,
+    /// number of seconds after the sale that the winning bid can be released to the seller if
+    /// the winner has not confirmed delivery
+    pub delivery_timeout: u64,
+}
+
+/// terms for deposit-backed bids.  A forfeited deposit is paid out like the proceeds of a sale,
+/// but never streamed
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct DepositTerms {
+    /// minimum deposit in basis points of the bid
+    pub rate_bps: u16,
+    /// number of seconds the winner has to pay the balance of their bid
+    pub payment_window: u64,
+}
+
+/// terms for retracting bids
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct RetractionTerms {
+    /// fee in basis points of the retracted bid that is kept by the auction
+    #[serde(default)]
+    pub fee_bps: u16,
+    /// true if the retraction fee is paid to the platform fee collector instead of the seller
+    #[serde(default)]
+    pub to_fee_collector: bool,
+    /// Optional scheduled end of the auction, in seconds since epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ends_at: Option<u64>,
+    /// number of seconds before the scheduled end from which bids can not be retracted until the
+    /// auction closes
+    #[serde(default)]
+    pub lock_period: u64,
+}
+
+/// flat fee kept from the first deposit of every new bidder
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct BidFee {
+    /// amount of the fee, in the unit of account if there are several bid tokens.  It is charged
+    /// in whichever token is bid, converted at that token's rate and rounded up
+    pub amount: Uint128,
+    /// true if the fee is paid to the platform fee collector instead of the seller
+    #[serde(default)]
+    pub to_fee_collector: bool,
 }
 
 /// Handle messages
@@ -40,21 +344,113 @@ pub enum HandleMsg {
         from: HumanAddr,
         /// amount of tokens sent
         amount: Uint128,
+        /// Optional base64 encoded BidOptions sent with the Send call when placing a bid -- not
+        /// needed or used when consigning
+        #[serde(default)]
+        msg: Option<Binary>,
+    },
+
+    /// ReceiveNft gets called by the SNIP-721 contract of the NFT being sold when the NFT is sent to
+    /// the auction, and will consign the NFT
+    ReceiveNft {
+        /// address that sent the NFT to the auction
+        sender: HumanAddr,
+        /// ID of the NFT sent
+        token_id: String,
+        /// Optional base64 encoded message sent with the SendNft call -- not needed or used by
+        /// this contract
+        #[serde(default)]
+        msg: Option<Binary>,
+    },
+
+    /// Snip1155Receive gets called by the SNIP-1155 contract of the tokens being sold when they are
+    /// sent to the auction, and will consign the sent tokens
+    Snip1155Receive {
+        /// address that sent the tokens to the auction
+        sender: HumanAddr,
+        /// ID of the tokens sent
+        token_id: String,
+        /// address of the owner of the tokens sent to the auction
+        from: HumanAddr,
+        /// amount of tokens sent
+        amount: Uint128,
+        /// Optional memo sent with the transfer -- not needed or used by this contract
+        #[serde(default)]
+        memo: Option<String>,
         /// Optional base64 encoded message sent with the Send call -- not needed or used by this
         /// contract
         #[serde(default)]
         msg: Option<Binary>,
     },
 
+    /// PlaceBid is used to bid native SCRT.  The bid is the amount of uscrt sent with the message
+    PlaceBid {
+        /// Optional code hash and message used to Send the won tokens to a contract
+        #[serde(default)]
+        payout: Option<PayoutCallback>,
+        /// Optional full amount of the bid when only a deposit is being sent
+        #[serde(default)]
+        bid_amount: Option<Uint128>,
+        /// Optional address that referred the bidder
+        #[serde(default)]
+        referrer: Option<HumanAddr>,
+        /// Optional id of the consortium bid the SCRT is contributed to
+        #[serde(default)]
+        consortium: Option<String>,
+    },
+
+    /// Consign is used to consign native SCRT for sale.  The amount consigned is the amount of
+    /// uscrt sent with the message
+    Consign {},
+
+    /// ConsignAllowance is used by the seller of an auction consigned by allowance to have the
+    /// auction verify that it has been granted an allowance for the full sale amount, and that the
+    /// seller holds that amount
+    ConsignAllowance {
+        /// the seller's viewing key with the sell token, used to check the seller's balance
+        balance_key: String,
+    },
+
+    /// WithdrawConsignment lets the seller, or a co-seller, pull back consigned tokens while the
+    /// auction is open and there are no active bids.  Co-sellers can only withdraw what they
+    /// consigned.  If the auction has an approver set, the withdrawal waits for the approvers'
+    /// consent
+    WithdrawConsignment {
+        /// Optional amount to withdraw.  Withdraws everything the caller consigned if omitted.
+        /// Only available when selling a single token
+        #[serde(default)]
+        amount: Option<Uint128>,
+    },
+
     /// RetractBid will retract any active bid the calling address has made and return the tokens
     /// that are held in escrow
     RetractBid {},
 
+    /// RetractContribution will withdraw the calling address' contribution to a consortium bid
+    /// and return the tokens that are held in escrow
+    RetractContribution {
+        /// id of the consortium
+        consortium: String,
+    },
+
     /// ViewBid will display the amount of the active bid made by the calling address and time the
     /// bid was placed
     ViewBid {},
 
-    /// Finalize will close the auction
+    /// ViewReferral will display the referral reward the calling address earned from the winning
+    /// bid
+    ViewReferral {},
+
+    /// ViewContribution will display the calling address' contribution to a consortium bid, and
+    /// the share of the sold tokens it receives if the consortium wins
+    ViewContribution {
+        /// id of the consortium
+        consortium: String,
+    },
+
+    /// Finalize will close the auction.  If the auction has an approver set, this records the
+    /// calling approver's approval, and the auction will close once enough approvals have been
+    /// collected.  Approvals are cleared whenever a bid is placed, raised or retracted
     Finalize {
         /// true if auction creator wants to keep the auction open if there are no active bids
         only_if_bids: bool,
@@ -64,6 +460,148 @@ pub enum HandleMsg {
     /// those funds to their owners.  Should never be needed, but included in case of unforeseen
     /// error
     ReturnAll {},
+
+    /// ProposeSellerTransfer lets the seller nominate a new address to take over ownership of the
+    /// auction.  Ownership does not change until the nominated address accepts.  If the auction
+    /// has an approver set, the nomination waits for the approvers' consent
+    ProposeSellerTransfer {
+        /// address being proposed as the new seller
+        address: HumanAddr,
+    },
+
+    /// AcceptSellerTransfer is called by the proposed seller to take over ownership of the
+    /// auction.  All proceeds and returned consignment will then go to the new seller
+    AcceptSellerTransfer {},
+
+    /// ApproveChange is called by an approver to consent to the change that is waiting for the
+    /// approvers' consent.  The change is made once enough approvals have been collected
+    ApproveChange {
+        /// the change being approved, which must match the pending change shown in auction_info
+        change: PendingChange,
+    },
+
+    /// SetOperator lets the seller authorize an address to perform the listed actions on the
+    /// seller's behalf.  An empty permission list removes the operator.  Operators never receive
+    /// any proceeds or returned consignment
+    SetOperator {
+        /// address of the operator
+        address: HumanAddr,
+        /// actions the operator is allowed to perform
+        permissions: Vec<OperatorPermission>,
+    },
+
+    /// ApproveCoSeller lets the seller approve an address to consign sell tokens alongside the
+    /// seller.  Co-sellers receive the proceeds, or have unsold consignment returned, pro-rata to
+    /// what they consigned
+    ApproveCoSeller {
+        /// address of the co-seller
+        address: HumanAddr,
+    },
+
+    /// CancelAuction will close the auction without a sale, returning all active bids to the
+    /// bidders and any consigned tokens to the seller.  If the auction has an approver set, this
+    /// records the calling approver's approval, and the auction will be cancelled once enough
+    /// approvals have been collected
+    CancelAuction {},
+
+    /// SetPaused can be called by the seller or admin to pause or resume the auction.  While
+    /// paused, all new bids and consignment are returned.  Retracting bids, finalizing, and
+    /// return_all are unaffected
+    SetPaused {
+        /// true to pause, false to resume
+        paused: bool,
+    },
+
+    /// SetPayoutAddresses can be called by the seller before the auction closes to change where
+    /// the proceeds are sent and where unsold consignment is returned.  Omitting an address means
+    /// the seller's address will be used.  If the auction has an approver set, the change waits
+    /// for the approvers' consent
+    SetPayoutAddresses {
+        /// Optional address that receives the proceeds instead of the seller
+        #[serde(default)]
+        proceeds_address: Option<HumanAddr>,
+        /// Optional address that unsold consignment is returned to instead of the seller
+        #[serde(default)]
+        consignment_return_address: Option<HumanAddr>,
+        /// Optional code hash and message used to Send the proceeds to a contract
+        #[serde(default)]
+        proceeds_callback: Option<PayoutCallback>,
+    },
+
+    /// Claim is called by the winner to collect won SNIP-20 tokens that are paid out with a
+    /// callback, or the tokens of a vesting schedule that have unlocked so far
+    Claim {
+        /// true to receive the tokens with a plain transfer instead of the payout callback, in
+        /// case the receiving contract rejects them
+        #[serde(default)]
+        plain_transfer: bool,
+    },
+
+    /// DeclareDefault can be called by anyone once the winner of an auction with deposit-backed
+    /// bids has failed to pay the balance within the payment window.  The winner's deposit is
+    /// forfeited to the seller, and the lot is offered to the next highest bidder
+    DeclareDefault {},
+
+    /// Dispute can be called by the seller or the winner while a sale is held for the dispute
+    /// period.  The sale will then only complete or reverse when the arbiter resolves the dispute
+    Dispute {},
+
+    /// ReleaseSale can be called by anyone to complete a held sale once the dispute period has
+    /// ended without a dispute
+    ReleaseSale {
+        /// true to send the proceeds with a plain transfer instead of the proceeds callback, in
+        /// case the receiving contract rejects them.  Only used when called by the seller
+        #[serde(default)]
+        plain_transfer: bool,
+    },
+
+    /// ConfirmDelivery is called by the winner of off-chain goods to release the winning bid to the
+    /// seller
+    ConfirmDelivery {
+        /// true to send the proceeds with a plain transfer instead of the proceeds callback, in
+        /// case the receiving contract rejects them
+        #[serde(default)]
+        plain_transfer: bool,
+    },
+
+    /// ResolveDispute is called by the arbiter to complete or reverse a disputed sale.  The
+    /// arbiter of off-chain goods can resolve the sale at any time before it is released
+    ResolveDispute {
+        /// true to complete the sale, false to refund the winner and return the consignment
+        complete: bool,
+        /// true to send the proceeds with a plain transfer instead of the proceeds callback, in
+        /// case the receiving contract rejects them
+        #[serde(default)]
+        plain_transfer: bool,
+    },
+
+    /// ClaimProceeds is called by the seller of an auction that streams its proceeds to collect
+    /// the proceeds that have accrued so far
+    ClaimProceeds {
+        /// true to receive the proceeds with a plain transfer instead of the proceeds callback,
+        /// in case the receiving contract rejects them
+        #[serde(default)]
+        plain_transfer: bool,
+    },
+
+    /// ChangeDescription will replace the auction's description
+    ChangeDescription {
+        /// new description of the auction.  Omit to remove the description
+        #[serde(default)]
+        description: Option<String>,
+    },
+}
+
+/// actions the seller can delegate to an operator
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum OperatorPermission {
+    /// may finalize the auction
+    Finalize,
+    /// may cancel the auction
+    Cancel,
+    /// may change the auction description
+    UpdateDescription,
 }
 
 /// Queries
@@ -72,6 +610,16 @@ pub enum HandleMsg {
 pub enum QueryMsg {
     /// Displays the auction information
     AuctionInfo {},
+    /// Displays the vested and claimed amounts of an auction with a vesting schedule
+    VestingInfo {
+        /// current time in seconds since epoch (queries do not have access to the block time)
+        time: u64,
+    },
+    /// Displays the accrued and claimed amounts of an auction that streams its proceeds
+    ProceedsInfo {
+        /// current time in seconds since epoch (queries do not have access to the block time)
+        time: u64,
+    },
 }
 
 /// responses to queries
@@ -79,39 +627,178 @@ pub enum QueryMsg {
 #[serde(rename_all = "snake_case")]
 pub enum QueryAnswer {
     /// AuctionInfo query response
-    AuctionInfo {
-        /// sell token address and TokenInfo query response
-        sell_token: Token,
-        /// bid token address and TokenInfo query response
-        bid_token: Token,
-        /// amount of tokens being sold
-        sell_amount: Uint128,
-        /// minimum bid that will be accepted
-        minimum_bid: Uint128,
-        /// Optional String description of auction
+    AuctionInfo(Box<AuctionInfo>),
+    /// VestingInfo query response
+    VestingInfo {
+        /// vesting schedule of the auction
+        schedule: VestingSchedule,
+        /// time the sale was finalized in seconds since epoch.  Omitted if there has not been a
+        /// sale
         #[serde(skip_serializing_if = "Option::is_none")]
-        description: Option<String>,
-        /// address of auction contract
-        auction_address: HumanAddr,
-        /// status of the auction can be "Accepting bids: Tokens to be sold have(not) been
-        /// consigned" or "Closed" (will also state if there are outstanding funds after auction
-        /// closure
-        status: String,
-        /// If the auction resulted in a swap, this will state the winning bid
+        start: Option<u64>,
+        /// amount of tokens being vested
+        total: Uint128,
+        /// amount of tokens unlocked at the queried time
+        vested: Uint128,
+        /// amount of tokens the winner has claimed
+        claimed: Uint128,
+    },
+    /// ProceedsInfo query response
+    ProceedsInfo {
+        /// number of seconds the proceeds are streamed over
+        duration: u64,
+        /// time the sale was finalized in seconds since epoch.  Omitted if there has not been a
+        /// sale
         #[serde(skip_serializing_if = "Option::is_none")]
-        winning_bid: Option<Uint128>,
+        start: Option<u64>,
+        /// amount of proceeds being streamed, after any platform fee
+        total: Uint128,
+        /// amount of proceeds accrued at the queried time
+        accrued: Uint128,
+        /// amount of proceeds the seller has claimed
+        claimed: Uint128,
     },
 }
 
+/// AuctionInfo query response.  Boxed in QueryAnswer to keep the other responses small
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+pub struct AuctionInfo {
+    /// sell token address and TokenInfo query response if selling a single token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sell_token: Option<Token>,
+    /// bid token address and TokenInfo query response if there is a single bid token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bid_token: Option<Token>,
+    /// tokens accepted for bids and their conversion rates if there are several bid tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bid_tokens: Option<Vec<BidTokenRate>>,
+    /// amount of tokens being sold if selling a single token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sell_amount: Option<Uint128>,
+    /// assets being sold if selling a bundle
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bundle: Option<Vec<BundleToken>>,
+    /// minimum bid that will be accepted, in the unit of account if there are several bid
+    /// tokens
+    pub minimum_bid: Uint128,
+    /// Optional String description of auction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// address of auction contract
+    pub auction_address: HumanAddr,
+    /// status of the auction can be "Accepting bids: Tokens to be sold have(not) been
+    /// consigned" or "Closed" (will also state if there are outstanding funds after auction
+    /// closure
+    pub status: String,
+    /// If the auction resulted in a swap, this will state the winning bid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub winning_bid: Option<Uint128>,
+    /// If the auction resulted in a swap and there are several bid tokens, this will state the
+    /// contract address of the token the winning bid was made in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub winning_bid_token: Option<HumanAddr>,
+    /// Optional set of addresses that must approve finalizing the auction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approvers: Option<ApproverSet>,
+    /// If an approver set was configured, the approvers who have called finalize since the bids
+    /// last changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approvals: Option<Vec<HumanAddr>>,
+    /// If an approver set was configured, the approvers who have called cancel_auction so far
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cancel_approvals: Option<Vec<HumanAddr>>,
+    /// Optional change to the auction that is waiting for the approvers' consent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending_change: Option<PendingChange>,
+    /// If a change is waiting for the approvers' consent, the approvers who have consented so far
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_approvals: Option<Vec<HumanAddr>>,
+    /// Optional platform fee taken out of the winning bid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee: Option<FeeConfig>,
+    /// If the auction resulted in a swap with a platform fee, this will state the fee paid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_paid: Option<Uint128>,
+    /// Optional list of addresses the proceeds are split between
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payees: Option<Vec<Payee>>,
+    /// Optional address that receives the proceeds instead of the seller
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proceeds_address: Option<HumanAddr>,
+    /// Optional address that unsold consignment is returned to instead of the seller
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consignment_return_address: Option<HumanAddr>,
+    /// Optional code hash and message used to Send the proceeds to a contract
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proceeds_callback: Option<PayoutCallback>,
+    /// Optional address that resolves disputes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arbiter: Option<HumanAddr>,
+    /// Optional number of seconds a sale is held for disputes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dispute_period: Option<u64>,
+    /// If a sale is being held, the time it can be released if it is not disputed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_time: Option<u64>,
+    /// Optional description of off-chain goods being sold
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub off_chain_goods: Option<String>,
+    /// Optional terms for deposit-backed bids
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deposit: Option<DepositTerms>,
+    /// If the winner is paying the balance of a deposit-backed bid, the time payment is due
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_deadline: Option<u64>,
+    /// Optional retraction fee and lock period
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retraction: Option<RetractionTerms>,
+    /// Optional flat fee kept from the first deposit of every new bidder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bid_fee: Option<BidFee>,
+    /// Optional rate in basis points of the proceeds paid to the referrer of the winning bid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub referral_rate_bps: Option<u16>,
+    /// true if the auction accepts consortium bids
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consortium_bids: Option<bool>,
+    /// Optional addresses approved to consign sell tokens alongside the seller
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub co_sellers: Option<Vec<HumanAddr>>,
+}
+
 /// token's contract address and TokenInfo response
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 pub struct Token {
-    /// contract address of token
-    pub contract_address: HumanAddr,
+    /// contract address of token.  Omitted if the token is native SCRT
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract_address: Option<HumanAddr>,
+    /// ID of the token if it is a SNIP-721 NFT or a SNIP-1155 token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_id: Option<String>,
     /// Tokeninfo query response
     pub token_info: TokenInfo,
 }
 
+/// a token accepted for bids and its conversion rate
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+pub struct BidTokenRate {
+    /// token address and TokenInfo query response
+    pub token: Token,
+    /// fixed conversion rate from this token to the auction's unit of account
+    pub rate: ConversionRate,
+}
+
+/// an asset in a bundle being sold and how much of it has been consigned
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+pub struct BundleToken {
+    /// token address and TokenInfo query response
+    pub token: Token,
+    /// amount of tokens being sold
+    pub amount: Uint128,
+    /// amount of tokens consigned so far
+    pub consigned: Uint128,
+}
+
 /// success or failure response
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 pub enum ResponseStatus {
@@ -154,6 +841,41 @@ pub enum HandleAnswer {
         /// Optional amount of tokens returned from escrow
         #[serde(skip_serializing_if = "Option::is_none")]
         amount_returned: Option<Uint128>,
+        /// Optional amount of tokens escrowed as a deposit for the bid
+        #[serde(skip_serializing_if = "Option::is_none")]
+        deposit: Option<Uint128>,
+        /// Optional placement fee kept from the tokens sent
+        #[serde(skip_serializing_if = "Option::is_none")]
+        fee: Option<Uint128>,
+    },
+    /// response from viewing a referral reward
+    Referral {
+        /// success or failure
+        status: ResponseStatus,
+        /// execution description
+        message: String,
+        /// Optional rate in basis points of the proceeds paid to the referrer of the winning bid
+        #[serde(skip_serializing_if = "Option::is_none")]
+        referral_rate_bps: Option<u16>,
+        /// Optional amount of the winning bid token the calling address earned
+        #[serde(skip_serializing_if = "Option::is_none")]
+        earned: Option<Uint128>,
+    },
+    /// response from viewing a contribution to a consortium bid
+    Contribution {
+        /// success or failure
+        status: ResponseStatus,
+        /// execution description
+        message: String,
+        /// Optional total amount of the consortium bid
+        #[serde(skip_serializing_if = "Option::is_none")]
+        total: Option<Uint128>,
+        /// Optional amount the calling address contributed
+        #[serde(skip_serializing_if = "Option::is_none")]
+        contribution: Option<Uint128>,
+        /// Optional amount of the sold tokens the calling address receives if the consortium wins
+        #[serde(skip_serializing_if = "Option::is_none")]
+        share: Option<Uint128>,
     },
     /// response from closing the auction
     CloseAuction {
@@ -167,6 +889,19 @@ pub enum HandleAnswer {
         /// Optional amount of tokens returned form escrow
         #[serde(skip_serializing_if = "Option::is_none")]
         amount_returned: Option<Uint128>,
+        /// Optional platform fee taken out of the winning bid
+        #[serde(skip_serializing_if = "Option::is_none")]
+        fee: Option<Uint128>,
+    },
+    /// response from attempt to claim vested tokens or streamed proceeds
+    Claim {
+        /// success or failure
+        status: ResponseStatus,
+        /// execution description
+        message: String,
+        /// Optional amount of tokens claimed
+        #[serde(skip_serializing_if = "Option::is_none")]
+        amount_claimed: Option<Uint128>,
     },
     /// response from attempt to retract bid
     RetractBid {
@@ -177,6 +912,9 @@ pub enum HandleAnswer {
         /// Optional amount of tokens returned from escrow
         #[serde(skip_serializing_if = "Option::is_none")]
         amount_returned: Option<Uint128>,
+        /// Optional retraction fee kept from the bid
+        #[serde(skip_serializing_if = "Option::is_none")]
+        fee: Option<Uint128>,
     },
     /// generic status response
     Status {
@@ -187,8 +925,416 @@ pub enum HandleAnswer {
     },
 }
 
+/// a token that is bid or sold in the auction
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub enum Asset {
+    /// SNIP-20 token
+    Snip20(ContractInfo),
+    /// native SCRT, denominated in uscrt
+    Native,
+    /// SNIP-721 NFT
+    Snip721(NftInfo),
+    /// SNIP-1155 token
+    Snip1155(Snip1155Info),
+}
+
+impl Asset {
+    /// Returns Asset built from an optional SNIP-20 contract, where None means native SCRT
+    ///
+    /// # Arguments
+    ///
+    /// * `contract` - optional code hash and address of a SNIP-20 contract
+    pub fn from_contract(contract: Option<ContractInfo>) -> Self {
+        match contract {
+            Some(info) => Asset::Snip20(info),
+            None => Asset::Native,
+        }
+    }
+
+    /// Returns true if the address is this asset's SNIP-20 token contract
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - a reference to the address to check
+    pub fn is_snip20(&self, address: &HumanAddr) -> bool {
+        match self {
+            Asset::Snip20(info) => info.address == *address,
+            _ => false,
+        }
+    }
+
+    /// Returns true if the two assets would be sent to the auction the same way, so the auction
+    /// could not tell them apart
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - a reference to the asset to compare with
+    pub fn conflicts_with(&self, other: &Asset) -> bool {
+        match (self, other) {
+            (Asset::Snip20(a), Asset::Snip20(b)) => a.address == b.address,
+            (Asset::Native, Asset::Native) => true,
+            (Asset::Snip721(a), Asset::Snip721(b)) => {
+                a.contract.address == b.contract.address && a.token_id == b.token_id
+            }
+            (Asset::Snip1155(a), Asset::Snip1155(b)) => {
+                a.contract.address == b.contract.address && a.token_id == b.token_id
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns a StdResult<Option<CosmosMsg>> used to register the auction with the asset's
+    /// contract, or None if the asset does not have a contract
+    ///
+    /// # Arguments
+    ///
+    /// * `code_hash` - String holding code hash of the auction contract
+    pub fn register_receive_msg(&self, code_hash: String) -> StdResult<Option<CosmosMsg>> {
+        match self {
+            Asset::Snip20(info) => info.register_receive_msg(code_hash).map(Some),
+            Asset::Native => Ok(None),
+            Asset::Snip721(nft) => Snip721HandleMsg::RegisterReceiveNft {
+                code_hash,
+                also_implements_batch_receive_nft: None,
+            }
+            .to_cosmos_msg(
+                nft.contract.code_hash.clone(),
+                nft.contract.address.clone(),
+                None,
+            )
+            .map(Some),
+            Asset::Snip1155(token) => Snip1155HandleMsg::RegisterReceive { code_hash }
+                .to_cosmos_msg(
+                    token.contract.code_hash.clone(),
+                    token.contract.address.clone(),
+                    None,
+                )
+                .map(Some),
+        }
+    }
+
+    /// Returns StdResult<CosmosMsg> that sends tokens held by the auction.  SNIP-20 tokens are
+    /// sent with Send if a callback is provided, and every other asset is transferred
+    ///
+    /// # Arguments
+    ///
+    /// * `auction_addr` - a reference to the address of the auction contract
+    /// * `recipient` - address receiving the tokens, unless the callback names another
+    /// * `amount` - amount of tokens to send
+    /// * `callback` - optional code hash and message for the receiving contract
+    pub fn payout_msg(
+        &self,
+        auction_addr: &HumanAddr,
+        recipient: HumanAddr,
+        amount: Uint128,
+        callback: Option<&PayoutCallback>,
+    ) -> StdResult<CosmosMsg> {
+        match (self, callback) {
+            (Asset::Snip20(info), Some(callback)) => Snip20HandleMsg::Send {
+                recipient: callback.recipient.clone().unwrap_or(recipient),
+                recipient_code_hash: Some(callback.code_hash.clone()),
+                amount,
+                msg: callback.msg.clone(),
+                padding: None,
+            }
+            .to_cosmos_msg(info.code_hash.clone(), info.address.clone(), None),
+            _ => self.transfer_msg(auction_addr, recipient, amount),
+        }
+    }
+
+    /// Returns a StdResult<CosmosMsg> used to send this asset from the auction's escrow
+    ///
+    /// # Arguments
+    ///
+    /// * `auction_addr` - a reference to the address of the auction contract
+    /// * `recipient` - address tokens are to be sent to
+    /// * `amount` - Uint128 amount of tokens to send (ignored for NFTs)
+    pub fn transfer_msg(
+        &self,
+        auction_addr: &HumanAddr,
+        recipient: HumanAddr,
+        amount: Uint128,
+    ) -> StdResult<CosmosMsg> {
+        match self {
+            Asset::Snip20(info) => info.transfer_msg(recipient, amount),
+            Asset::Native => Ok(CosmosMsg::Bank(BankMsg::Send {
+                from_address: auction_addr.clone(),
+                to_address: recipient,
+                amount: vec![Coin {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount,
+                }],
+            })),
+            Asset::Snip721(nft) => Snip721HandleMsg::TransferNft {
+                recipient,
+                token_id: nft.token_id.clone(),
+            }
+            .to_cosmos_msg(
+                nft.contract.code_hash.clone(),
+                nft.contract.address.clone(),
+                None,
+            ),
+            Asset::Snip1155(token) => Snip1155HandleMsg::Transfer {
+                token_id: token.token_id.clone(),
+                from: auction_addr.clone(),
+                recipient,
+                amount,
+                memo: None,
+            }
+            .to_cosmos_msg(
+                token.contract.code_hash.clone(),
+                token.contract.address.clone(),
+                None,
+            ),
+        }
+    }
+
+    /// Returns a StdResult<Token> with the asset's contract address and token information
+    ///
+    /// # Arguments
+    ///
+    /// * `querier` - a reference to the Querier dependency of the querying contract
+    pub fn token<Q: Querier>(&self, querier: &Q) -> StdResult<Token> {
+        match self {
+            Asset::Snip20(info) => Ok(Token {
+                contract_address: Some(info.address.clone()),
+                token_id: None,
+                token_info: info.token_info_query(querier)?,
+            }),
+            Asset::Native => Ok(Token {
+                contract_address: None,
+                token_id: None,
+                token_info: TokenInfo {
+                    name: String::from("Secret"),
+                    symbol: String::from("SCRT"),
+                    decimals: 6,
+                    total_supply: None,
+                },
+            }),
+            Asset::Snip721(nft) => {
+                let resp: NftContractInfoResponse = Snip721QueryMsg::ContractInfo {}.query(
+                    querier,
+                    nft.contract.code_hash.clone(),
+                    nft.contract.address.clone(),
+                )?;
+                Ok(Token {
+                    contract_address: Some(nft.contract.address.clone()),
+                    token_id: Some(nft.token_id.clone()),
+                    token_info: TokenInfo {
+                        name: resp.contract_info.name,
+                        symbol: resp.contract_info.symbol,
+                        decimals: 0,
+                        total_supply: None,
+                    },
+                })
+            }
+            Asset::Snip1155(token) => {
+                let resp: TokenIdPublicInfoResponse = Snip1155QueryMsg::TokenIdPublicInfo {
+                    token_id: token.token_id.clone(),
+                }
+                .query(
+                    querier,
+                    token.contract.code_hash.clone(),
+                    token.contract.address.clone(),
+                )?;
+                let info = resp.token_id_public_info.token_id_info;
+                let decimals = match info.token_config {
+                    TokenIdConfig::Fungible { decimals } => decimals,
+                    TokenIdConfig::Nft {} => 0,
+                };
+                Ok(Token {
+                    contract_address: Some(token.contract.address.clone()),
+                    token_id: Some(token.token_id.clone()),
+                    token_info: TokenInfo {
+                        name: info.name,
+                        symbol: info.symbol,
+                        decimals,
+                        total_supply: resp.token_id_public_info.total_supply,
+                    },
+                })
+            }
+        }
+    }
+}
+
+/// SNIP-20 handle messages sent by the auction that are not provided by secret-toolkit
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Snip20HandleMsg {
+    /// send tokens held by the auction to a contract
+    Send {
+        /// address receiving the tokens
+        recipient: HumanAddr,
+        /// code hash of the receiving contract
+        recipient_code_hash: Option<String>,
+        /// amount of tokens to send
+        amount: Uint128,
+        /// optional message passed to the receiving contract
+        msg: Option<Binary>,
+        /// optional padding
+        padding: Option<String>,
+    },
+}
+
+impl HandleCallback for Snip20HandleMsg {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+}
+
+/// SNIP-721 contract and token ID of an NFT
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+pub struct NftInfo {
+    /// code hash and address of the SNIP-721 contract
+    pub contract: ContractInfo,
+    /// ID of the NFT
+    pub token_id: String,
+}
+
+/// SNIP-721 handle messages sent by the auction
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Snip721HandleMsg {
+    /// register the auction to receive ReceiveNft callbacks
+    RegisterReceiveNft {
+        /// code hash of the auction contract
+        code_hash: String,
+        /// true if the auction implements BatchReceiveNft
+        also_implements_batch_receive_nft: Option<bool>,
+    },
+    /// transfer an NFT held by the auction
+    TransferNft {
+        /// address receiving the NFT
+        recipient: HumanAddr,
+        /// ID of the NFT
+        token_id: String,
+    },
+}
+
+impl HandleCallback for Snip721HandleMsg {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+}
+
+/// SNIP-721 queries made by the auction
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Snip721QueryMsg {
+    /// name and symbol of the NFT contract
+    ContractInfo {},
+}
+
+impl Query for Snip721QueryMsg {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+}
+
+/// wrapper of the SNIP-721 ContractInfo query response
+#[derive(Deserialize)]
+pub struct NftContractInfoResponse {
+    /// SNIP-721 ContractInfo query response
+    pub contract_info: NftContractInfo,
+}
+
+/// SNIP-721 ContractInfo query response
+#[derive(Deserialize)]
+pub struct NftContractInfo {
+    /// name of the NFT contract
+    pub name: String,
+    /// symbol of the NFT contract
+    pub symbol: String,
+}
+
+/// SNIP-1155 contract and token ID
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+pub struct Snip1155Info {
+    /// code hash and address of the SNIP-1155 contract
+    pub contract: ContractInfo,
+    /// ID of the token
+    pub token_id: String,
+}
+
+/// SNIP-1155 handle messages sent by the auction
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Snip1155HandleMsg {
+    /// register the auction to receive Snip1155Receive callbacks
+    RegisterReceive {
+        /// code hash of the auction contract
+        code_hash: String,
+    },
+    /// transfer tokens held by the auction
+    Transfer {
+        /// ID of the tokens
+        token_id: String,
+        /// address the tokens are sent from
+        from: HumanAddr,
+        /// address receiving the tokens
+        recipient: HumanAddr,
+        /// amount of tokens to send
+        amount: Uint128,
+        /// optional memo
+        memo: Option<String>,
+    },
+}
+
+impl HandleCallback for Snip1155HandleMsg {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+}
+
+/// SNIP-1155 queries made by the auction
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Snip1155QueryMsg {
+    /// public information about a token ID
+    TokenIdPublicInfo {
+        /// ID of the token
+        token_id: String,
+    },
+}
+
+impl Query for Snip1155QueryMsg {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+}
+
+/// wrapper of the SNIP-1155 TokenIdPublicInfo query response
+#[derive(Deserialize)]
+pub struct TokenIdPublicInfoResponse {
+    /// SNIP-1155 TokenIdPublicInfo query response
+    pub token_id_public_info: TokenIdPublicInfo,
+}
+
+/// SNIP-1155 TokenIdPublicInfo query response
+#[derive(Deserialize)]
+pub struct TokenIdPublicInfo {
+    /// information about the token ID
+    pub token_id_info: TokenIdInfo,
+    /// total supply of the token ID, if public
+    pub total_supply: Option<Uint128>,
+}
+
+/// name, symbol, and configuration of a SNIP-1155 token ID
+#[derive(Deserialize)]
+pub struct TokenIdInfo {
+    /// name of the token
+    pub name: String,
+    /// symbol of the token
+    pub symbol: String,
+    /// configuration of the token
+    pub token_config: TokenIdConfig,
+}
+
+/// configuration of a SNIP-1155 token ID
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenIdConfig {
+    /// fungible token
+    Fungible {
+        /// number of decimal places
+        decimals: u8,
+    },
+    /// non-fungible token
+    Nft {},
+}
+
 /// code hash and address of a contract
-#[derive(Serialize, Deserialize, JsonSchema)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
 pub struct ContractInfo {
     /// contract's code hash string
     pub code_hash: String,
@@ -2,11 +2,14 @@ use std::{any::type_name, collections::HashSet};
 
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use cosmwasm_std::{HumanAddr, ReadonlyStorage, StdError, StdResult, Storage};
+use cosmwasm_std::{CanonicalAddr, HumanAddr, ReadonlyStorage, StdError, StdResult, Storage};
 
 use secret_toolkit::serialization::{Bincode2, Serde};
 
-use crate::msg::ContractInfo;
+use crate::msg::{
+    ApproverSet, Asset, BidFee, DepositTerms, FeeConfig, OperatorPermission, Payee, PayoutCallback,
+    PendingChange, RetractionTerms, VestingSchedule,
+};
 
 /// state of the auction
 #[derive(Serialize, Deserialize)]
@@ -15,26 +18,339 @@ pub struct State {
     pub auction_addr: HumanAddr,
     /// address of auction owner
     pub seller: HumanAddr,
-    /// code hash and address of sell token contract
-    pub sell_contract: ContractInfo,
-    /// code hash and address of bid token contract
-    pub bid_contract: ContractInfo,
-    /// amount of tokens for sale
-    pub sell_amount: u128,
-    /// minimum bid that will be accepted
+    /// address the seller has proposed to transfer auction ownership to
+    pub pending_seller: Option<HumanAddr>,
+    /// addresses authorized to act on the seller's behalf
+    pub operators: Vec<Operator>,
+    /// assets being sold
+    pub lot: Vec<LotItem>,
+    /// tokens accepted for bids
+    pub bid_tokens: Vec<BidToken>,
+    /// minimum bid that will be accepted, in the unit of account if there are several bid tokens
     pub minimum_bid: u128,
-    /// amount of tokens currently consigned to auction escrow
-    pub currently_consigned: u128,
     /// list of addresses of bidders
     pub bidders: HashSet<Vec<u8>>,
     /// true if the auction is closed
     pub is_completed: bool,
-    /// true if all tokens for sale have been consigned to escrow
+    /// true if all tokens for sale have been consigned to escrow or are backed by an allowance
     pub tokens_consigned: bool,
     /// Optional text description of auction
     pub description: Option<String>,
     /// winning bid
     pub winning_bid: u128,
+    /// index of the bid token the winning bid was made in
+    pub winning_bid_token: usize,
+    /// optional set of addresses that must approve finalizing the auction
+    pub approvers: Option<ApproverSet>,
+    /// approvers who have called finalize since the bids last changed
+    pub approvals: Vec<HumanAddr>,
+    /// approvers who have called cancel_auction so far
+    pub cancel_approvals: Vec<HumanAddr>,
+    /// change to the auction that is waiting for the approvers' consent
+    pub pending_change: Option<PendingChange>,
+    /// approvers who have consented to the pending change
+    pub change_approvals: Vec<HumanAddr>,
+    /// optional address that can pause the auction in addition to the seller
+    pub admin: Option<HumanAddr>,
+    /// true if new bids and consignment are being refused
+    pub is_paused: bool,
+    /// viewing key the auction set with the sell token if it is consigned by allowance
+    pub allowance_key: Option<String>,
+    /// seller's viewing key with the sell token, used to check the seller's balance
+    pub seller_key: Option<String>,
+    /// optional platform fee taken out of the winning bid
+    pub fee: Option<FeeConfig>,
+    /// platform fee taken out of the winning bid
+    pub fee_paid: u128,
+    /// addresses the proceeds are split between.  Empty if the seller receives all the proceeds
+    pub payees: Vec<Payee>,
+    /// optional address that receives the proceeds instead of the seller
+    pub proceeds_address: Option<HumanAddr>,
+    /// optional address that unsold consignment is returned to instead of the seller
+    pub consignment_return_address: Option<HumanAddr>,
+    /// optional code hash and message used to Send the proceeds to a contract
+    pub proceeds_callback: Option<PayoutCallback>,
+    /// address of the winning bidder
+    pub winner: Option<HumanAddr>,
+    /// optional code hash and message the winner asked to have the won tokens sent with
+    pub winner_payout: Option<PayoutCallback>,
+    /// true if won SNIP-20 tokens are waiting in escrow for the winner to claim them
+    pub payout_unclaimed: bool,
+    /// optional schedule for releasing the sold tokens to the winner
+    pub vesting: Option<VestingSchedule>,
+    /// time the vesting schedule started
+    pub vesting_start: Option<u64>,
+    /// amount of vested tokens the winner has claimed
+    pub vesting_claimed: u128,
+    /// optional number of seconds the proceeds are streamed to the seller over
+    pub stream_duration: Option<u64>,
+    /// time the proceeds stream started
+    pub stream_start: Option<u64>,
+    /// amount of proceeds being streamed
+    pub stream_total: u128,
+    /// amount of streamed proceeds the seller has claimed
+    pub stream_claimed: u128,
+    /// optional address that resolves disputes over a sale
+    pub arbiter: Option<HumanAddr>,
+    /// number of seconds a sale is held before it can be released (the delivery timeout for
+    /// off-chain goods)
+    pub dispute_period: u64,
+    /// sale being held for the dispute period or until delivery is confirmed
+    pub held_sale: Option<HeldSale>,
+    /// metadata describing the off-chain goods being sold, if the lot is off-chain
+    pub off_chain_goods: Option<String>,
+    /// optional terms for deposit-backed bids
+    pub deposit: Option<DepositTerms>,
+    /// winning deposit-backed bid waiting for the balance to be paid
+    pub awaiting_payment: Option<PendingPayment>,
+    /// optional retraction fee and lock period
+    pub retraction: Option<RetractionTerms>,
+    /// optional flat fee kept from the first deposit of every new bidder
+    pub bid_fee: Option<BidFee>,
+    /// optional rate in basis points of the proceeds paid to the referrer of the winning bid
+    pub referral_rate_bps: Option<u16>,
+    /// addresses allowed to earn referral rewards
+    pub referrers: Vec<CanonicalAddr>,
+    /// address that referred the winning bid
+    pub referrer: Option<HumanAddr>,
+    /// referral reward paid out of the proceeds
+    pub referral_paid: u128,
+    /// true if several addresses can pool their bid tokens into a shared consortium bid
+    pub consortium_bids: bool,
+    /// ids of the active consortium bids
+    pub consortia: HashSet<String>,
+    /// id of the consortium that won the auction
+    pub winning_consortium: Option<String>,
+    /// addresses approved to consign sell tokens alongside the seller
+    pub co_sellers: Vec<HumanAddr>,
+    /// sell tokens each co-seller has consigned.  The seller consigned the rest
+    pub co_consignments: Vec<Consignment>,
+}
+
+impl State {
+    /// Returns true if the address is the seller or an operator holding the permission
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - a reference to the address attempting the action
+    /// * `permission` - the permission needed to perform the action
+    pub fn is_authorized(&self, address: &HumanAddr, permission: OperatorPermission) -> bool {
+        *address == self.seller
+            || self
+                .operators
+                .iter()
+                .any(|op| op.address == *address && op.permissions.contains(&permission))
+    }
+
+    /// Returns StdResult<u128> of the amount of sold tokens unlocked for the winner at the given
+    /// time
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - current time in seconds since epoch
+    pub fn vested(&self, time: u64) -> StdResult<u128> {
+        match (&self.vesting, self.vesting_start) {
+            (Some(schedule), Some(start)) => released(
+                self.lot[0].amount,
+                time.saturating_sub(start),
+                schedule.cliff,
+                schedule.duration,
+            ),
+            _ => Ok(0),
+        }
+    }
+
+    /// Returns StdResult<u128> of the amount of streamed proceeds accrued for the seller at the
+    /// given time
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - current time in seconds since epoch
+    pub fn streamed(&self, time: u64) -> StdResult<u128> {
+        match (self.stream_duration, self.stream_start) {
+            (Some(duration), Some(start)) => {
+                released(self.stream_total, time.saturating_sub(start), 0, duration)
+            }
+            _ => Ok(0),
+        }
+    }
+
+    /// Returns HumanAddr of the address that receives the proceeds
+    pub fn proceeds_recipient(&self) -> HumanAddr {
+        self.proceeds_address
+            .clone()
+            .unwrap_or_else(|| self.seller.clone())
+    }
+
+    /// Returns HumanAddr of the address that receives fees kept from bids
+    ///
+    /// # Arguments
+    ///
+    /// * `to_fee_collector` - true if the fee goes to the platform fee collector
+    pub fn bid_fee_recipient(&self, to_fee_collector: bool) -> HumanAddr {
+        match (&self.fee, to_fee_collector) {
+            (Some(fee), true) => fee.collector.clone(),
+            _ => self.proceeds_recipient(),
+        }
+    }
+
+    /// Clears the approvals to finalize, which were given for the bids as they stood.  Approvals
+    /// are kept once the auction has closed
+    pub fn clear_approvals(&mut self) {
+        if !self.is_completed {
+            self.approvals.clear();
+        }
+    }
+
+    /// Returns bool true if bids are locked in because the auction is open and the lock period
+    /// before its scheduled end has started.  Bids stay locked until the auction closes, even if
+    /// it is finalized after the scheduled end
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - current block time in seconds since epoch
+    pub fn retraction_locked(&self, time: u64) -> bool {
+        match (&self.retraction, self.is_completed) {
+            (
+                Some(RetractionTerms {
+                    ends_at: Some(ends_at),
+                    lock_period,
+                    ..
+                }),
+                false,
+            ) => ends_at.saturating_sub(*lock_period) <= time,
+            _ => false,
+        }
+    }
+
+    /// Returns StdResult<u128> of the fee kept from a retracted amount.  The fee is only charged
+    /// while the auction is open, so bids left in escrow after it closes are returned in full
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - amount being retracted
+    pub fn retraction_fee(&self, amount: u128) -> StdResult<u128> {
+        match (&self.retraction, self.is_completed) {
+            (Some(terms), false) => amount
+                .checked_mul(terms.fee_bps as u128)
+                .map(|product| product / 10000)
+                .ok_or_else(|| StdError::generic_err("Retraction fee is too large")),
+            _ => Ok(0),
+        }
+    }
+
+    /// Returns HumanAddr of the address that unsold consignment is returned to
+    pub fn consignment_recipient(&self) -> HumanAddr {
+        self.consignment_return_address
+            .clone()
+            .unwrap_or_else(|| self.seller.clone())
+    }
+}
+
+/// Returns StdResult<u128> of the amount released on a cliff/linear schedule
+///
+/// # Arguments
+///
+/// * `total` - total amount being released
+/// * `elapsed` - seconds since the schedule started
+/// * `cliff` - seconds before anything is released
+/// * `duration` - seconds until everything is released
+fn released(total: u128, elapsed: u64, cliff: u64, duration: u64) -> StdResult<u128> {
+    if elapsed < cliff {
+        Ok(0)
+    } else if elapsed >= duration {
+        Ok(total)
+    } else {
+        total
+            .checked_mul(elapsed as u128)
+            .map(|product| product / duration as u128)
+            .ok_or_else(|| StdError::generic_err("Released amount is too large"))
+    }
+}
+
+/// an asset in the lot being sold
+#[derive(Serialize, Deserialize)]
+pub struct LotItem {
+    /// token being sold
+    pub asset: Asset,
+    /// amount of tokens for sale
+    pub amount: u128,
+    /// amount of tokens currently consigned to auction escrow, or backed by an allowance
+    pub consigned: u128,
+}
+
+/// a token accepted for bids
+#[derive(Serialize, Deserialize)]
+pub struct BidToken {
+    /// token being bid
+    pub asset: Asset,
+    /// value of `rate_denominator` tokens in the auction's unit of account
+    pub rate_numerator: u128,
+    /// number of tokens worth `rate_numerator` in the auction's unit of account
+    pub rate_denominator: u128,
+}
+
+impl BidToken {
+    /// Returns StdResult<u128> of the value of an amount of this token in the auction's unit of
+    /// account, rounded down
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - amount of this token
+    pub fn value(&self, amount: u128) -> StdResult<u128> {
+        amount
+            .checked_mul(self.rate_numerator)
+            .map(|product| product / self.rate_denominator)
+            .ok_or_else(|| StdError::generic_err("Bid value is too large"))
+    }
+
+    /// Returns StdResult<u128> of the amount of this token worth at least a value in the
+    /// auction's unit of account, rounded up
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - value in the auction's unit of account
+    pub fn amount_worth(&self, value: u128) -> StdResult<u128> {
+        value
+            .checked_mul(self.rate_denominator)
+            .map(|product| {
+                product / self.rate_numerator + (product % self.rate_numerator != 0) as u128
+            })
+            .ok_or_else(|| StdError::generic_err("Fee is too large"))
+    }
+}
+
+/// an address authorized to act on the seller's behalf
+#[derive(Serialize, Deserialize)]
+pub struct Operator {
+    /// address of the operator
+    pub address: HumanAddr,
+    /// actions the operator is allowed to perform
+    pub permissions: Vec<OperatorPermission>,
+}
+
+/// a sale held in escrow for the dispute period
+#[derive(Serialize, Deserialize)]
+pub struct HeldSale {
+    /// address of the winning bidder
+    pub winner: HumanAddr,
+    /// the winning bid
+    pub bid: Bid,
+    /// time the sale can be released if it is not disputed
+    pub release_time: u64,
+    /// true if the seller or winner has disputed the sale
+    pub disputed: bool,
+}
+
+/// a winning deposit-backed bid waiting for the balance to be paid
+#[derive(Serialize, Deserialize)]
+pub struct PendingPayment {
+    /// address of the winning bidder
+    pub winner: HumanAddr,
+    /// the winning bid
+    pub bid: Bid,
+    /// time the balance must be paid by
+    pub deadline: u64,
 }
 
 /// bid data
@@ -44,6 +360,116 @@ pub struct Bid {
     pub amount: u128,
     /// time bid was placed
     pub timestamp: u64,
+    /// index of the bid token used
+    pub token: usize,
+    /// value of the bid in the auction's unit of account
+    pub value: u128,
+    /// optional code hash and message used to Send the won tokens to a contract
+    pub payout: Option<PayoutCallback>,
+    /// amount escrowed if only a deposit of the bid has been paid
+    pub deposit: Option<u128>,
+    /// address that referred the bidder
+    pub referrer: Option<CanonicalAddr>,
+}
+
+impl Bid {
+    /// Returns u128 of the amount of bid tokens held in escrow for this bid
+    pub fn escrowed(&self) -> u128 {
+        self.deposit.unwrap_or(self.amount)
+    }
+}
+
+/// sell tokens a co-seller consigned to escrow
+#[derive(Serialize, Deserialize)]
+pub struct Consignment {
+    /// address of the co-seller
+    pub consignor: HumanAddr,
+    /// amount of sell tokens consigned
+    pub amount: u128,
+}
+
+/// a bid pooled from several contributors
+#[derive(Serialize, Deserialize)]
+pub struct Consortium {
+    /// the shared bid
+    pub bid: Bid,
+    /// bid tokens each address contributed
+    pub contributions: Vec<Contribution>,
+}
+
+impl Consortium {
+    /// Returns StdResult<Vec<u128>> of each contributor's pro-rata share of the sold tokens.  Any
+    /// remainder from rounding goes to the first contributor
+    ///
+    /// # Arguments
+    ///
+    /// * `lot_amount` - amount of tokens being sold
+    pub fn shares(&self, lot_amount: u128) -> StdResult<Vec<u128>> {
+        let mut shares = Vec::new();
+        for contribution in &self.contributions {
+            shares.push(
+                mul_div(lot_amount, contribution.amount, self.bid.amount)
+                    .ok_or_else(|| StdError::generic_err("Consortium share is too large"))?,
+            );
+        }
+        let dust = lot_amount - shares.iter().sum::<u128>();
+        if let Some(first) = shares.first_mut() {
+            *first += dust;
+        }
+        Ok(shares)
+    }
+}
+
+/// an address' contribution to a consortium bid
+#[derive(Serialize, Deserialize)]
+pub struct Contribution {
+    /// address of the contributor
+    pub contributor: HumanAddr,
+    /// amount of bid tokens contributed
+    pub amount: u128,
+}
+
+/// Returns Option<u128> of amount * numerator / denominator rounded down, computed with a 256-bit
+/// intermediate product so that large amounts of 18-decimal tokens do not overflow.  None if the
+/// denominator is 0 or the result does not fit in a u128
+///
+/// # Arguments
+///
+/// * `amount` - amount being scaled
+/// * `numerator` - numerator of the ratio
+/// * `denominator` - denominator of the ratio
+pub fn mul_div(amount: u128, numerator: u128, denominator: u128) -> Option<u128> {
+    if denominator == 0 {
+        return None;
+    }
+    // multiply the 64-bit halves to get the product as high and low 128-bit words
+    let mask = u64::MAX as u128;
+    let (a_high, a_low) = (amount >> 64, amount & mask);
+    let (n_high, n_low) = (numerator >> 64, numerator & mask);
+    let low_low = a_low * n_low;
+    let high_low = a_high * n_low;
+    let low_high = a_low * n_high;
+    let middle = (low_low >> 64) + (high_low & mask) + (low_high & mask);
+    let low = (middle << 64) | (low_low & mask);
+    let high = a_high * n_high + (high_low >> 64) + (low_high >> 64) + (middle >> 64);
+    // the quotient only fits in 128 bits if the high word is less than the denominator
+    if high >= denominator {
+        return None;
+    }
+    // long division of the low word, one bit at a time, starting with the high word as the
+    // remainder
+    let mut remainder = high;
+    let mut quotient = 0u128;
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= denominator {
+            remainder = remainder.wrapping_sub(denominator);
+            quotient |= 1;
+        }
+    }
+    Some(quotient)
 }
 
 /// Returns StdResult<()> resulting from saving an item to storage
@@ -99,3 +525,64 @@ pub fn may_load<T: DeserializeOwned, S: ReadonlyStorage>(
         None => Ok(None),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn consortium(amounts: &[u128]) -> Consortium {
+        Consortium {
+            bid: Bid {
+                amount: amounts.iter().sum(),
+                timestamp: 0,
+                token: 0,
+                value: amounts.iter().sum(),
+                payout: None,
+                deposit: None,
+                referrer: None,
+            },
+            contributions: amounts
+                .iter()
+                .enumerate()
+                .map(|(i, amount)| Contribution {
+                    contributor: HumanAddr(format!("contributor{}", i)),
+                    amount: *amount,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn consortium_shares_give_dust_to_first_contributor() {
+        let shares = consortium(&[1, 1, 1]).shares(100).unwrap();
+        assert_eq!(shares, vec![34, 33, 33]);
+        assert_eq!(shares.iter().sum::<u128>(), 100);
+    }
+
+    #[test]
+    fn consortium_shares_are_pro_rata() {
+        let shares = consortium(&[300, 100]).shares(1000).unwrap();
+        assert_eq!(shares, vec![750, 250]);
+    }
+
+    #[test]
+    fn mul_div_keeps_full_precision() {
+        // a billion 18-decimal tokens squared overflows a u128
+        let large = 1_000_000_000 * 10u128.pow(18);
+        assert_eq!(mul_div(large, large, large), Some(large));
+        assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX), Some(u128::MAX));
+        assert_eq!(mul_div(u128::MAX, 3, 4), Some(u128::MAX / 4 * 3 + 2));
+        assert_eq!(mul_div(7, 3, 2), Some(10));
+        assert_eq!(mul_div(u128::MAX, 2, 1), None);
+        assert_eq!(mul_div(1, 1, 0), None);
+    }
+
+    #[test]
+    fn consortium_shares_of_18_decimal_tokens() {
+        let unit = 10u128.pow(18);
+        let shares = consortium(&[3_000_000 * unit, 1_000_000 * unit])
+            .shares(1_000_000_000 * unit)
+            .unwrap();
+        assert_eq!(shares, vec![750_000_000 * unit, 250_000_000 * unit]);
+    }
+}